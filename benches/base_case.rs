@@ -0,0 +1,52 @@
+//! Compares the scans of the wide base case against a plain
+//! word-by-word loop over the same words.
+//!
+//! Run with `--features simd` and `RUSTFLAGS="-C target-cpu=native"`
+//! to measure the AVX2 scans, and without them for the scalar ones.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use flat_veb::{InnerVEBTree, SizedVEBTree};
+
+type T = SizedVEBTree<12>;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // A single element at the far end makes
+    // every scan walk over all the words.
+    let mut tree = T::new();
+    tree.insert(T::CAPACITY - 1);
+    let mut words = [0u64; T::CAPACITY / 64];
+    words[words.len() - 1] = 1 << 63;
+
+    let mut dense = T::new();
+    dense.insert_range(0, T::CAPACITY);
+    let dense_words = [!0u64; T::CAPACITY / 64];
+
+    let mut group = c.benchmark_group("base_case-scan");
+    group.bench_function(BenchmarkId::new("next", "tree"), |b| {
+        b.iter(|| black_box(&tree).next(black_box(0)));
+    });
+    group.bench_function(BenchmarkId::new("next", "loop"), |b| {
+        b.iter(|| black_box(&words).iter().position(|&word| word != 0));
+    });
+    group.bench_function(BenchmarkId::new("prev", "tree"), |b| {
+        b.iter(|| black_box(&tree).prev(black_box(T::CAPACITY - 2)));
+    });
+    group.bench_function(BenchmarkId::new("is_empty", "tree"), |b| {
+        b.iter(|| black_box(&tree).is_empty());
+    });
+    group.bench_function(BenchmarkId::new("len", "tree"), |b| {
+        b.iter(|| black_box(&dense).len());
+    });
+    group.bench_function(BenchmarkId::new("len", "loop"), |b| {
+        b.iter(|| {
+            black_box(&dense_words)
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum::<usize>()
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
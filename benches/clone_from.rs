@@ -0,0 +1,39 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use flat_veb::{InnerVEBTree, SizedVEBTree};
+use rand::{distributions::Uniform, prelude::StdRng, Rng, SeedableRng};
+
+/// Makes a tree with a few random elements,
+/// boxed since the bigger trees don't fit well on the stack.
+fn sparse<T: InnerVEBTree>(rng: &mut StdRng) -> Box<T> {
+    let distr = Uniform::from(0..T::CAPACITY);
+    let mut tree = Box::<T>::default();
+    for _ in 0..64 {
+        tree.insert(rng.sample(distr));
+    }
+    tree
+}
+
+fn bench_size<T: InnerVEBTree>(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let source = sparse::<T>(&mut rng);
+    let mut target = sparse::<T>(&mut rng);
+
+    let mut group = c.benchmark_group("clone_from-sparse");
+    group.bench_function(BenchmarkId::new("derived", T::BITS), |b| {
+        // What the derived clone_from does.
+        b.iter(|| *target = black_box(*source));
+    });
+    group.bench_function(BenchmarkId::new("custom", T::BITS), |b| {
+        b.iter(|| target.clone_from(black_box(&source)));
+    });
+    group.finish();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_size::<SizedVEBTree<16>>(c);
+    bench_size::<SizedVEBTree<20>>(c);
+    bench_size::<SizedVEBTree<24>>(c);
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use flat_veb::{SizedVEBTree, VEBTree};
+use rand::{distributions::Uniform, prelude::StdRng, Rng, SeedableRng};
+
+type T = SizedVEBTree<20>;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let distr = Uniform::from(0..1 << 20);
+
+    let mut sparse = Box::<T>::default();
+    for _ in 0..1000 {
+        sparse.insert(rng.sample(distr));
+    }
+    let mut dense = Box::<T>::default();
+    for _ in 0..1 << 19 {
+        dense.insert(rng.sample(distr));
+    }
+
+    let mut group = c.benchmark_group("count");
+    for (name, tree) in [("sparse", &sparse), ("dense", &dense)] {
+        group.bench_function(BenchmarkId::new("count", name), |b| {
+            b.iter(|| black_box(&**tree).count());
+        });
+        group.bench_function(BenchmarkId::new("iter-count", name), |b| {
+            b.iter(|| black_box(&**tree).iter().count());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use flat_veb::{InnerVEBTree, SizedVEBTree};
+use rand::{distributions::Uniform, prelude::StdRng, Rng, SeedableRng};
+
+fn bench_size<T: InnerVEBTree + FromIterator<usize>>(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let distr = Uniform::from(0..T::CAPACITY);
+
+    let mut group = c.benchmark_group(format!("from_sorted-{}", T::BITS));
+    for len in [1 << 8, 1 << 12, 1 << 16] {
+        let mut xs = (0..len).map(|_| rng.sample(distr)).collect::<Vec<_>>();
+        xs.sort_unstable();
+
+        group.bench_function(BenchmarkId::new("from_sorted_slice", len), |b| {
+            b.iter(|| T::from_sorted_slice(black_box(&xs)));
+        });
+        group.bench_function(BenchmarkId::new("collect", len), |b| {
+            b.iter(|| black_box(&xs).iter().copied().collect::<T>());
+        });
+    }
+    group.finish();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_size::<SizedVEBTree<16>>(c);
+    bench_size::<SizedVEBTree<20>>(c);
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
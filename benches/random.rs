@@ -132,6 +132,21 @@ fn criterion_benchmark(c: &mut Criterion) {
         black_box(s.prev(x))
     });
 
+    for_all_widths(
+        c.benchmark_group(format!("insert_range-veb")),
+        veb_maker,
+        |s, x| s.insert_range(x, (x + 256).min(s.capacity())),
+    );
+    for_all_widths(
+        c.benchmark_group(format!("insert_range-loop-veb")),
+        veb_maker,
+        |s, x| {
+            for y in x..(x + 256).min(s.capacity()) {
+                s.insert(y);
+            }
+        },
+    );
+
     for_all_widths(
         c.benchmark_group(format!("insert-btree")),
         btree_maker,
@@ -0,0 +1,242 @@
+use crate::{
+    bit_array::BitArraySet,
+    outer,
+    private::ConditionalHasDeepMaybeUninit,
+    small_set::{Bits, SmallSet},
+    InnerVEBTree, VEBIterator, VEBTree,
+};
+use rkyv::{
+    out_field,
+    ser::{ScratchSpace, Serializer},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Archived, Deserialize, Fallible, Serialize,
+};
+
+/// Archived form of a `VEBTree`, storing the number of bits
+/// of the tree and its elements in ascending order.
+///
+/// Queries are answered with binary search directly on the
+/// archived bytes, so they take O(log n) time instead of O(log log U),
+/// but the tree doesn't have to be deserialized first.
+///
+/// ```
+/// use flat_veb::{SizedVEBTree, VEBTree};
+/// use rkyv::Deserialize;
+///
+/// let mut tree = SizedVEBTree::<16>::new();
+/// tree.insert(42);
+/// tree.insert(1337);
+///
+/// let bytes = rkyv::to_bytes::<_, 256>(&tree).unwrap();
+/// let archived = unsafe { rkyv::archived_root::<SizedVEBTree<16>>(&bytes) };
+/// assert!(archived.contains(42));
+/// assert_eq!(archived.next(43), Some(1337));
+///
+/// let back: SizedVEBTree<16> = archived.deserialize(&mut rkyv::Infallible).unwrap();
+/// assert_eq!(back, tree);
+/// ```
+#[derive(Debug)]
+#[repr(C)]
+pub struct ArchivedVEBTree {
+    bits: Archived<u32>,
+    elements: ArchivedVec<Archived<u64>>,
+}
+
+/// The resolver for an `ArchivedVEBTree`.
+pub struct VEBTreeResolver {
+    elements: VecResolver,
+}
+
+/// `VecResolver` doesn't implement `Debug`,
+/// so the position it holds is left out.
+impl core::fmt::Debug for VEBTreeResolver {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("VEBTreeResolver").finish_non_exhaustive()
+    }
+}
+
+/// Converts an archived element back to a `usize`.
+/// It was a `usize` when it was archived, so it fits.
+#[allow(clippy::cast_possible_truncation)]
+fn element(x: Archived<u64>) -> usize {
+    x as usize
+}
+
+impl ArchivedVEBTree {
+    /// Returns the capacity of the archived tree.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        1 << self.bits
+    }
+
+    /// Returns the number of elements in the archived tree.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns true if the archived tree has no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the number of elements in the archived tree less than x.
+    #[must_use]
+    pub fn rank(&self, x: usize) -> usize {
+        self.elements.partition_point(|&y| y < x as u64)
+    }
+
+    /// Returns true if x is in the archived tree.
+    #[must_use]
+    pub fn contains(&self, x: usize) -> bool {
+        self.next(x) == Some(x)
+    }
+
+    /// Returns the smallest element in the archived tree that is >= x.
+    #[must_use]
+    pub fn next(&self, x: usize) -> Option<usize> {
+        self.elements.get(self.rank(x)).copied().map(element)
+    }
+
+    /// Returns the largest element in the archived tree that is <= x.
+    #[must_use]
+    pub fn prev(&self, x: usize) -> Option<usize> {
+        let i = self.rank(x.saturating_add(1)).checked_sub(1)?;
+        Some(element(self.elements[i]))
+    }
+
+    /// Returns the smallest element in the archived tree.
+    #[must_use]
+    pub fn first(&self) -> Option<usize> {
+        self.elements.first().copied().map(element)
+    }
+
+    /// Returns the largest element in the archived tree.
+    #[must_use]
+    pub fn last(&self) -> Option<usize> {
+        self.elements.last().copied().map(element)
+    }
+
+    /// Iterates over the elements of the archived tree in ascending order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = usize> + ExactSizeIterator + '_ {
+        self.elements.iter().copied().map(element)
+    }
+
+    /// Inserts the archived elements into an empty tree.
+    fn fill(&self, tree: &mut dyn VEBTree) {
+        debug_assert_eq!(tree.capacity(), self.capacity());
+        for x in self.iter() {
+            tree.insert(x);
+        }
+    }
+}
+
+fn serialize_tree<S: ScratchSpace + Serializer + ?Sized>(
+    tree: &dyn VEBTree,
+    serializer: &mut S,
+) -> Result<VEBTreeResolver, S::Error> {
+    Ok(VEBTreeResolver {
+        elements: ArchivedVec::serialize_from_iter::<u64, u64, _, _>(
+            VEBIterator::new(tree).map(|x| x as u64),
+            serializer,
+        )?,
+    })
+}
+
+/// # Safety
+///
+/// Has the same requirements as `Archive::resolve`.
+unsafe fn resolve_tree(
+    tree: &dyn VEBTree,
+    pos: usize,
+    resolver: VEBTreeResolver,
+    out: *mut ArchivedVEBTree,
+) {
+    let (fp, fo) = out_field!(out.bits);
+    tree.capacity().trailing_zeros().resolve(pos + fp, (), fo);
+    let (fp, fo) = out_field!(out.elements);
+    ArchivedVec::resolve_from_len(tree.len(), pos + fp, resolver.elements, fo);
+}
+
+impl<const BITS: usize, T: Bits> Archive for SmallSet<BITS, T> {
+    type Archived = ArchivedVEBTree;
+    type Resolver = VEBTreeResolver;
+
+    unsafe fn resolve(&self, pos: usize, resolver: Self::Resolver, out: *mut Self::Archived) {
+        resolve_tree(self, pos, resolver, out);
+    }
+}
+
+impl<S: ScratchSpace + Serializer + ?Sized, const BITS: usize, T: Bits> Serialize<S>
+    for SmallSet<BITS, T>
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        serialize_tree(self, serializer)
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> Archive for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    type Archived = ArchivedVEBTree;
+    type Resolver = VEBTreeResolver;
+
+    unsafe fn resolve(&self, pos: usize, resolver: Self::Resolver, out: *mut Self::Archived) {
+        resolve_tree(self, pos, resolver, out);
+    }
+}
+
+impl<S: ScratchSpace + Serializer + ?Sized, const BITS: usize, const WORDS: usize> Serialize<S>
+    for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        serialize_tree(self, serializer)
+    }
+}
+
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Archive
+    for outer::VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    type Archived = ArchivedVEBTree;
+    type Resolver = VEBTreeResolver;
+
+    unsafe fn resolve(&self, pos: usize, resolver: Self::Resolver, out: *mut Self::Archived) {
+        resolve_tree(self, pos, resolver, out);
+    }
+}
+
+impl<S, const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Serialize<S>
+    for outer::VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        serialize_tree(self, serializer)
+    }
+}
+
+impl<T: InnerVEBTree, D: Fallible + ?Sized> Deserialize<T, D> for ArchivedVEBTree {
+    fn deserialize(&self, _: &mut D) -> Result<T, D::Error> {
+        let mut tree = T::default();
+        self.fill(&mut tree);
+        Ok(tree)
+    }
+}
+
+/// Deserializes into a tree with the capacity it was archived with,
+/// using `new_with_bits`.
+#[cfg(feature = "dyn_capacity")]
+impl<D: Fallible + ?Sized> Deserialize<alloc::boxed::Box<dyn VEBTree>, D> for ArchivedVEBTree {
+    fn deserialize(&self, _: &mut D) -> Result<alloc::boxed::Box<dyn VEBTree>, D::Error> {
+        let mut tree = crate::new_with_bits(self.bits as usize);
+        self.fill(&mut *tree);
+        Ok(tree)
+    }
+}
@@ -0,0 +1,635 @@
+use crate::{
+    private::{ConditionalHasDeepMaybeUninit, Sealed},
+    small_set::Bits,
+    word_scan, InnerVEBTree, IntoIter, VEBIterator, VEBTree,
+};
+#[cfg(feature = "dyn_capacity")]
+use deep_maybe_uninit::{DeepMaybeUninit, HasDeepMaybeUninit};
+#[cfg(feature = "rayon")]
+use rayon::iter::ParallelIterator;
+
+/// Wide base case implementation of `VEBTree`.
+/// Maintains a set of integers from
+/// 0 to (exclusive) `1 << BITS = WORDS * 64`,
+/// using an array of `u64` as a collection of flags.
+///
+/// Searches scan the words linearly, which is faster
+/// than recursing further for the sizes it is used for.
+#[cfg_attr(feature = "dyn_capacity", derive(DeepMaybeUninit))]
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct BitArraySet<const BITS: usize, const WORDS: usize>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    words: [u64; WORDS],
+}
+
+impl<const BITS: usize, const WORDS: usize> Sealed for BitArraySet<BITS, WORDS> where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit
+{
+}
+
+impl<const BITS: usize, const WORDS: usize> BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    pub fn new() -> Self {
+        debug_assert_eq!(WORDS * 64, Self::CAPACITY);
+        Self { words: [0; WORDS] }
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> Default for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> PartialEq for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.words == other.words
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> Eq for BitArraySet<BITS, WORDS> where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit
+{
+}
+
+impl<const BITS: usize, const WORDS: usize> core::hash::Hash for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for x in self.iter() {
+            x.hash(state);
+        }
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> core::fmt::Debug for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> core::fmt::Display for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        crate::fmt_runs(self, f)
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> FromIterator<usize> for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> Extend<usize> for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
+
+impl<'a, const BITS: usize, const WORDS: usize> Extend<&'a usize> for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn extend<I: IntoIterator<Item = &'a usize>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, const BITS: usize, const WORDS: usize> IntoIterator for &'a BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    type Item = usize;
+    type IntoIter = VEBIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> IntoIterator for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    type Item = usize;
+    type IntoIter = IntoIter<Self>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    pub fn capacity() -> usize {
+        Self::CAPACITY
+    }
+
+    /// Splits `x` into the index of its word and its bit in that word.
+    fn wb(x: usize) -> (usize, usize) {
+        (x >> 6, x & 63)
+    }
+
+    /// Calls `f` with each word overlapping [lo, hi),
+    /// and the flags for the elements of the range in that word.
+    /// hi is clamped to the capacity.
+    fn for_each_range_word(&mut self, lo: usize, hi: usize, mut f: impl FnMut(&mut u64, u64)) {
+        let hi = hi.min(Self::CAPACITY);
+        if lo >= hi {
+            return;
+        }
+        for w in lo >> 6..=(hi - 1) >> 6 {
+            let start = lo.max(w << 6) - (w << 6);
+            let end = hi.min((w + 1) << 6) - (w << 6);
+            f(
+                &mut self.words[w],
+                u64::low_bits(end) & !u64::low_bits(start),
+            );
+        }
+    }
+
+    #[cfg(feature = "dyn_capacity")]
+    pub fn init(value: &mut <Self as HasDeepMaybeUninit>::AsDeepMaybeUninit) {
+        for word in value.words.iter_mut() {
+            *word = 0u64.forget_init();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.words = [0; WORDS];
+    }
+
+    pub fn fill(&mut self) {
+        self.words = [!0; WORDS];
+    }
+
+    pub fn is_empty(&self) -> bool {
+        word_scan::position_nonzero(&self.words).is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        word_scan::count_ones(&self.words)
+    }
+
+    pub fn count(&self) -> usize {
+        self.len()
+    }
+
+    pub fn rank(&self, x: usize) -> usize {
+        if x >= Self::CAPACITY {
+            return self.len();
+        }
+        let (w, b) = Self::wb(x);
+        word_scan::count_ones(&self.words[..w])
+            + (self.words[w] & u64::low_bits(b)).count_ones() as usize
+    }
+
+    pub fn select(&self, k: usize) -> Option<usize> {
+        let mut k = k;
+        for (w, &word) in self.words.iter().enumerate() {
+            let ones = word.count_ones() as usize;
+            if k < ones {
+                let mut word = word;
+                for _ in 0..k {
+                    word &= word - 1;
+                }
+                return Some(w << 6 | word.trailing_zeros() as usize);
+            }
+            k -= ones;
+        }
+        None
+    }
+
+    pub fn contains(&self, x: usize) -> bool {
+        debug_assert!(x < Self::CAPACITY);
+        if x >= Self::CAPACITY {
+            return false;
+        }
+        let (w, b) = Self::wb(x);
+        self.words[w] >> b & 1 != 0
+    }
+
+    pub fn insert(&mut self, x: usize) -> bool {
+        let was = self.contains(x);
+        let (w, b) = Self::wb(x);
+        self.words[w] |= 1 << b;
+        !was
+    }
+
+    pub fn insert_and_return_neighbor(&mut self, x: usize) -> (bool, Option<usize>) {
+        let successor = if x + 1 < Self::CAPACITY {
+            self.next(x + 1)
+        } else {
+            None
+        };
+        (self.insert(x), successor)
+    }
+
+    pub fn insert_range(&mut self, lo: usize, hi: usize) {
+        self.for_each_range_word(lo, hi, |word, mask| *word |= mask);
+    }
+
+    pub fn remove(&mut self, x: usize) -> bool {
+        let was = self.contains(x);
+        let (w, b) = Self::wb(x);
+        self.words[w] &= !(1 << b);
+        was
+    }
+
+    pub fn toggle(&mut self, x: usize) -> bool {
+        debug_assert!(x < Self::CAPACITY);
+        let (w, b) = Self::wb(x);
+        self.words[w] ^= 1 << b;
+        self.words[w] >> b & 1 != 0
+    }
+
+    pub fn pop_first(&mut self) -> Option<usize> {
+        let x = self.first()?;
+        self.remove(x);
+        Some(x)
+    }
+
+    pub fn pop_last(&mut self) -> Option<usize> {
+        let x = self.last()?;
+        self.remove(x);
+        Some(x)
+    }
+
+    pub fn remove_range(&mut self, lo: usize, hi: usize) {
+        self.for_each_range_word(lo, hi, |word, mask| *word &= !mask);
+    }
+
+    pub fn toggle_range(&mut self, lo: usize, hi: usize) {
+        self.for_each_range_word(lo, hi, |word, mask| *word ^= mask);
+    }
+
+    pub fn next(&self, x: usize) -> Option<usize> {
+        debug_assert!(x < Self::CAPACITY);
+        if x >= Self::CAPACITY {
+            return None;
+        }
+        let (w, b) = Self::wb(x);
+        let big_enough = self.words[w] & !u64::low_bits(b);
+        if big_enough != 0 {
+            return Some(w << 6 | big_enough.trailing_zeros() as usize);
+        }
+        let w = w + 1 + word_scan::position_nonzero(&self.words[w + 1..])?;
+        Some(w << 6 | self.words[w].trailing_zeros() as usize)
+    }
+
+    pub fn prev(&self, x: usize) -> Option<usize> {
+        debug_assert!(x < Self::CAPACITY);
+        if x >= Self::CAPACITY {
+            return None;
+        }
+        let (w, b) = Self::wb(x);
+        let small_enough = self.words[w] & u64::low_bits(b + 1);
+        if small_enough != 0 {
+            return Some(w << 6 | (63 - small_enough.leading_zeros() as usize));
+        }
+        let w = word_scan::rposition_nonzero(&self.words[..w])?;
+        Some(w << 6 | (63 - self.words[w].leading_zeros() as usize))
+    }
+
+    pub fn next_gap(&self, x: usize) -> Option<usize> {
+        debug_assert!(x < Self::CAPACITY);
+        let (w, b) = Self::wb(x);
+        let missing = !self.words[w] & !u64::low_bits(b);
+        if missing != 0 {
+            return Some(w << 6 | missing.trailing_zeros() as usize);
+        }
+        let w = w + 1 + self.words[w + 1..].iter().position(|&word| word != !0)?;
+        Some(w << 6 | self.words[w].trailing_ones() as usize)
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= other;
+        }
+    }
+
+    pub fn difference_with(&mut self, other: &Self) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= !other;
+        }
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &Self) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word ^= other;
+        }
+    }
+
+    pub fn diff(&self, other: &Self) -> (Self, Self) {
+        (
+            Self {
+                words: core::array::from_fn(|w| self.words[w] & !other.words[w]),
+            },
+            Self {
+                words: core::array::from_fn(|w| other.words[w] & !self.words[w]),
+            },
+        )
+    }
+
+    pub fn complement(&self) -> Self {
+        Self {
+            words: self.words.map(|word| !word),
+        }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(word, other)| word & !other == 0)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(word, other)| word & other == 0)
+    }
+
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(word, other)| (word & other).count_ones() as usize)
+            .sum()
+    }
+
+    pub fn split_off(&mut self, x: usize) -> Self {
+        let mut other = *self;
+        self.remove_range(x, Self::CAPACITY);
+        other.remove_range(0, x);
+        other
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        self.union_with(other);
+        other.clear();
+    }
+
+    pub fn from_sorted_shifted(xs: &[usize], offset: usize) -> Self {
+        let mut set = Self::new();
+        for &x in xs {
+            debug_assert!(x - offset < Self::CAPACITY, "{x} does not fit in the set.");
+            let (w, b) = Self::wb(x - offset);
+            set.words[w] |= 1 << b;
+        }
+        set
+    }
+
+    pub fn from_bitmask(words: &[u64]) -> Self {
+        let mut set = Self::new();
+        set.words.copy_from_slice(words);
+        set
+    }
+
+    pub fn write_bitmask(&self, words: &mut [u64]) {
+        words.copy_from_slice(&self.words);
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = usize> + '_ {
+        // Too small to be worth splitting up.
+        rayon::iter::once(self).flat_map_iter(|set| set.iter())
+    }
+
+    pub fn first(&self) -> Option<usize> {
+        self.next(0)
+    }
+
+    pub fn last(&self) -> Option<usize> {
+        self.prev(Self::CAPACITY - 1)
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> InnerVEBTree for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    const BITS: usize = BITS;
+    const HEIGHT: usize = 0;
+
+    #[cfg(feature = "dyn_capacity")]
+    fn init(value: &mut <Self as HasDeepMaybeUninit>::AsDeepMaybeUninit) {
+        Self::init(value);
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        self.union_with(other);
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        self.intersect_with(other);
+    }
+
+    fn difference_with(&mut self, other: &Self) {
+        self.difference_with(other);
+    }
+
+    fn symmetric_difference_with(&mut self, other: &Self) {
+        self.symmetric_difference_with(other);
+    }
+
+    fn diff(&self, other: &Self) -> (Self, Self) {
+        self.diff(other)
+    }
+
+    fn complement(&self) -> Self {
+        self.complement()
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.is_subset(other)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.is_disjoint(other)
+    }
+
+    fn intersection_len(&self, other: &Self) -> usize {
+        self.intersection_len(other)
+    }
+
+    fn split_off(&mut self, x: usize) -> Self {
+        self.split_off(x)
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.append(other);
+    }
+
+    fn shift_by(&self, offset: isize) -> Option<Self> {
+        crate::shift_by_inserting(self, offset)
+    }
+
+    fn from_sorted_shifted(xs: &[usize], offset: usize) -> Self {
+        Self::from_sorted_shifted(xs, offset)
+    }
+
+    fn from_bitmask(words: &[u64]) -> Self {
+        Self::from_bitmask(words)
+    }
+
+    fn write_bitmask(&self, words: &mut [u64]) {
+        self.write_bitmask(words);
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_iter(&self) -> impl ParallelIterator<Item = usize> + '_ {
+        self.par_iter()
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> VEBTree for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn capacity(&self) -> usize {
+        Self::capacity()
+    }
+
+    fn bits(&self) -> usize {
+        <Self as InnerVEBTree>::BITS
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn core::any::Any {
+        self
+    }
+
+    fn as_dyn(&self) -> &dyn VEBTree {
+        self
+    }
+
+    fn clear(&mut self) {
+        self.clear();
+    }
+
+    fn fill(&mut self) {
+        self.fill();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn count(&self) -> usize {
+        self.count()
+    }
+
+    fn rank(&self, x: usize) -> usize {
+        self.rank(x)
+    }
+
+    fn select(&self, k: usize) -> Option<usize> {
+        self.select(k)
+    }
+
+    fn contains(&self, x: usize) -> bool {
+        self.contains(x)
+    }
+
+    fn insert(&mut self, x: usize) -> bool {
+        self.insert(x)
+    }
+
+    fn insert_and_return_neighbor(&mut self, x: usize) -> (bool, Option<usize>) {
+        self.insert_and_return_neighbor(x)
+    }
+
+    fn insert_range(&mut self, lo: usize, hi: usize) {
+        self.insert_range(lo, hi);
+    }
+
+    fn remove(&mut self, x: usize) -> bool {
+        self.remove(x)
+    }
+
+    fn toggle(&mut self, x: usize) -> bool {
+        self.toggle(x)
+    }
+
+    fn remove_range(&mut self, lo: usize, hi: usize) {
+        self.remove_range(lo, hi);
+    }
+
+    fn toggle_range(&mut self, lo: usize, hi: usize) {
+        self.toggle_range(lo, hi);
+    }
+
+    fn pop_first(&mut self) -> Option<usize> {
+        self.pop_first()
+    }
+
+    fn pop_last(&mut self) -> Option<usize> {
+        self.pop_last()
+    }
+
+    fn next(&self, x: usize) -> Option<usize> {
+        self.next(x)
+    }
+
+    fn prev(&self, x: usize) -> Option<usize> {
+        self.prev(x)
+    }
+
+    fn next_gap(&self, x: usize) -> Option<usize> {
+        self.next_gap(x)
+    }
+
+    fn first(&self) -> Option<usize> {
+        self.first()
+    }
+
+    fn last(&self) -> Option<usize> {
+        self.last()
+    }
+}
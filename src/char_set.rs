@@ -0,0 +1,149 @@
+use crate::{InnerVEBTree, SizedVEBTree, VEBTree};
+
+/// A set of `char`s, stored as their code points
+/// in a `SizedVEBTree<21>`.
+///
+/// The tree takes up a bit over 256 KB.
+///
+/// ```
+/// use flat_veb::CharSet;
+///
+/// let mut set: CharSet = "hello, wörld".chars().collect();
+/// assert!(set.contains('ö'));
+/// assert_eq!(set.next('i'), Some('l'));
+/// assert_eq!(set.prev('a'), Some(','));
+/// assert_eq!(set.last(), Some('ö'));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CharSet {
+    tree: SizedVEBTree<21>,
+}
+
+/// Converts a code point in the tree back into a `char`,
+/// or None if it is not a Unicode scalar value.
+fn to_char(x: usize) -> Option<char> {
+    u32::try_from(x).ok().and_then(char::from_u32)
+}
+
+impl CharSet {
+    /// Makes an empty set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the set of code points.
+    #[must_use]
+    pub fn inner(&self) -> &SizedVEBTree<21> {
+        &self.tree
+    }
+
+    /// Returns the number of chars in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns true if the set contains no chars.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Clears the set, removing all chars.
+    pub fn clear(&mut self) {
+        self.tree.clear();
+    }
+
+    /// Returns true if the set contains c.
+    #[must_use]
+    pub fn contains(&self, c: char) -> bool {
+        self.tree.contains(c as usize)
+    }
+
+    /// Adds c to the set.
+    ///
+    /// If the set did not have c present, true is returned.
+    pub fn insert(&mut self, c: char) -> bool {
+        self.tree.insert(c as usize)
+    }
+
+    /// Removes c from the set.
+    ///
+    /// If the set did have c present, true is returned.
+    pub fn remove(&mut self, c: char) -> bool {
+        self.tree.remove(c as usize)
+    }
+
+    /// Returns the first char in the set, if any.
+    #[must_use]
+    pub fn first(&self) -> Option<char> {
+        self.next_from(0)
+    }
+
+    /// Returns the last char in the set, if any.
+    #[must_use]
+    pub fn last(&self) -> Option<char> {
+        self.prev_from(SizedVEBTree::<21>::CAPACITY - 1)
+    }
+
+    /// Returns the smallest char in the set
+    /// that is greater or equal to c, if any.
+    #[must_use]
+    pub fn next(&self, c: char) -> Option<char> {
+        self.next_from(c as usize)
+    }
+
+    /// Returns the biggest char in the set
+    /// that is less or equal to c, if any.
+    #[must_use]
+    pub fn prev(&self, c: char) -> Option<char> {
+        self.prev_from(c as usize)
+    }
+
+    /// Returns the first valid char in the set
+    /// with code point at least x, if any.
+    fn next_from(&self, x: usize) -> Option<char> {
+        let mut x = self.tree.next(x)?;
+        loop {
+            if let Some(c) = to_char(x) {
+                return Some(c);
+            }
+            x = self.tree.next_excl(x)?;
+        }
+    }
+
+    /// Returns the last valid char in the set
+    /// with code point at most x, if any.
+    fn prev_from(&self, x: usize) -> Option<char> {
+        let mut x = self.tree.prev(x)?;
+        loop {
+            if let Some(c) = to_char(x) {
+                return Some(c);
+            }
+            x = self.tree.prev_excl(x)?;
+        }
+    }
+
+    /// Returns an iterator over the chars in the set,
+    /// in ascending order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = char> + '_ {
+        self.tree.iter().filter_map(to_char)
+    }
+}
+
+impl FromIterator<char> for CharSet {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<char> for CharSet {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        for c in iter {
+            self.insert(c);
+        }
+    }
+}
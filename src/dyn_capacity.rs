@@ -15,28 +15,74 @@ use deep_maybe_uninit::IsDeepMaybeUninit;
 /// the `T::default()` call inside `Box::default()`
 /// doesn't get inlined, leading to storing `T`
 /// on the stack.
+///
+/// Use this instead of `SizedVEBTree::<BITS>::new()`
+/// for large BITS, since `SizedVEBTree::<28>` is
+/// already bigger than the usual 8 MB main thread stack.
+/// ```
+/// use flat_veb::{SizedVEBTree, VEBTree};
+///
+/// let mut tree = flat_veb::new_boxed::<SizedVEBTree<28>>();
+/// tree.insert(1 << 27);
+/// assert_eq!(tree.first(), Some(1 << 27));
+/// ```
+#[must_use]
 pub fn new_boxed<T: InnerVEBTree>() -> Box<T> {
     let mut b = T::boxed_uninit();
     T::init(&mut b);
     unsafe { b.boxed_assume_init() }
 }
 
+/// The largest number of bits `new_with_capacity` has a type for.
+pub(crate) const MAX_BITS: usize = 49;
+
+/// The error returned by `try_new_with_capacity`
+/// when there is no type with the requested capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError {
+    requested: usize,
+    maximum: usize,
+}
+
+impl CapacityError {
+    /// The capacity that was asked for.
+    #[must_use]
+    pub fn requested(&self) -> usize {
+        self.requested
+    }
+
+    /// The highest capacity there is a type for.
+    #[must_use]
+    pub fn maximum(&self) -> usize {
+        self.maximum
+    }
+}
+
+impl core::fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Too high capacity: {}. The highest supported capacity is {}.",
+            self.requested, self.maximum
+        )
+    }
+}
+
 /// Get the smallest capacity `VEBTree` implementation which
 /// can hold integers of size at least `capacity - 1`.
 ///
-/// # Panics
+/// # Errors
 ///
-/// The function panics if given an absurdly high capacity,
+/// Returns a `CapacityError` if given an absurdly high capacity,
 /// because there is no type to return an instance of with that capacity.
 ///
 /// But it probably fails for other reasons for smaller
 /// capacities than that, like running out of memory.
-#[must_use]
-pub fn new_with_capacity(capacity: usize) -> Box<dyn VEBTree> {
+pub fn try_new_with_capacity(capacity: usize) -> Result<Box<dyn VEBTree>, CapacityError> {
     macro_rules! inner {
         ($n:expr, T T T T $($tail:tt)*) => {
             if capacity <= SizedVEBTree::<{ $n }>::CAPACITY {
-                return new_boxed::<SizedVEBTree<{ $n }>>();
+                return Ok(new_boxed::<SizedVEBTree<{ $n }>>());
             }
 
             inner! {($n+1), T T T $($tail)*}
@@ -44,7 +90,8 @@ pub fn new_with_capacity(capacity: usize) -> Box<dyn VEBTree> {
         ($n:expr, T T T) => {}
     }
 
-    inner! {4,
+    inner! {0,
+        T T T T
         T T T T T T T T
         T T T T T T T T
         T T T T T T T T
@@ -54,7 +101,26 @@ pub fn new_with_capacity(capacity: usize) -> Box<dyn VEBTree> {
         T
     }
 
-    panic!("Too high capacity: {capacity}.");
+    Err(CapacityError {
+        requested: capacity,
+        maximum: SizedVEBTree::<MAX_BITS>::CAPACITY,
+    })
+}
+
+/// Get the smallest capacity `VEBTree` implementation which
+/// can hold integers of size at least `capacity - 1`.
+///
+/// # Panics
+///
+/// The function panics if given an absurdly high capacity,
+/// because there is no type to return an instance of with that capacity.
+/// Use `try_new_with_capacity` to get an error instead.
+///
+/// But it probably fails for other reasons for smaller
+/// capacities than that, like running out of memory.
+#[must_use]
+pub fn new_with_capacity(capacity: usize) -> Box<dyn VEBTree> {
+    try_new_with_capacity(capacity).unwrap_or_else(|err| panic!("{err}"))
 }
 
 /// Get the smallest capacity `VEBTree` implementation which
@@ -0,0 +1,47 @@
+use crate::{
+    bit_array::BitArraySet,
+    outer,
+    private::ConditionalHasDeepMaybeUninit,
+    small_set::{Bits, SmallSet},
+    InnerVEBTree,
+};
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// The most elements put in a generated tree,
+/// so big trees don't take time proportional to their capacity.
+const MAX_ELEMENTS: usize = 1024;
+
+/// Makes a tree with a random number of random elements.
+fn arbitrary_tree<T: InnerVEBTree>(u: &mut Unstructured<'_>) -> Result<T> {
+    let mut tree = T::default();
+    let len = u.arbitrary_len::<usize>()?.min(MAX_ELEMENTS);
+    for _ in 0..len {
+        tree.insert(u.int_in_range(0..=T::CAPACITY - 1)?);
+    }
+    Ok(tree)
+}
+
+impl<'a, const BITS: usize, T: Bits> Arbitrary<'a> for SmallSet<BITS, T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_tree(u)
+    }
+}
+
+impl<'a, const BITS: usize, const WORDS: usize> Arbitrary<'a> for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_tree(u)
+    }
+}
+
+impl<'a, const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Arbitrary<'a>
+    for outer::VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_tree(u)
+    }
+}
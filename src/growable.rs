@@ -0,0 +1,177 @@
+use crate::{new_with_capacity, VEBIterator, VEBTree};
+use alloc::boxed::Box;
+
+/// A set of integers which moves to a bigger `VEBTree`
+/// when an integer too big for the current one is inserted,
+/// like a `Vec` growing when it is full.
+///
+/// Growing makes a new tree with at least twice the capacity,
+/// and inserts the elements of the old tree into it.
+/// Since the capacities grow geometrically,
+/// the total time spent growing is O(U) for the final capacity U,
+/// so it is amortized over the inserts that needed the bigger capacity.
+/// The other operations take the same time as in the inner tree.
+///
+/// ```
+/// let mut tree = flat_veb::GrowableVEBTree::new();
+/// tree.insert(3);
+/// tree.insert(1_000_000);
+///
+/// assert!(tree.capacity() > 1_000_000);
+/// assert_eq!(tree.next(4), Some(1_000_000));
+/// assert!(!tree.contains(5_000_000));
+/// ```
+#[derive(Debug)]
+pub struct GrowableVEBTree {
+    tree: Box<dyn VEBTree>,
+}
+
+impl Default for GrowableVEBTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GrowableVEBTree {
+    /// Makes an empty set with the smallest capacity.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Makes an empty set which can hold
+    /// integers in [0, capacity) before growing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_with_capacity(capacity)` panics.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            tree: new_with_capacity(capacity),
+        }
+    }
+
+    /// Returns the tree the elements are currently stored in.
+    #[must_use]
+    pub fn inner(&self) -> &dyn VEBTree {
+        &*self.tree
+    }
+
+    /// The set can hold integers in [0, capacity) without growing.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.tree.capacity()
+    }
+
+    /// Returns the number of elements in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Clears the set, removing all elements.
+    /// The capacity stays the same.
+    pub fn clear(&mut self) {
+        self.tree.clear();
+    }
+
+    /// Returns true if the set contains x.
+    /// Integers above the capacity are never contained.
+    #[must_use]
+    pub fn contains(&self, x: usize) -> bool {
+        x < self.capacity() && self.tree.contains(x)
+    }
+
+    /// Adds x to the set, growing it first
+    /// if x is not less than the capacity.
+    ///
+    /// If the set did not have x present, true is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if x is too big for any `VEBTree`,
+    /// like `new_with_capacity`.
+    pub fn insert(&mut self, x: usize) -> bool {
+        if x >= self.capacity() {
+            self.grow(x);
+        }
+        self.tree.insert(x)
+    }
+
+    /// Moves the elements to a tree which can hold x,
+    /// and which has at least twice the current capacity.
+    fn grow(&mut self, x: usize) {
+        let capacity = x.saturating_add(1).max(self.capacity().saturating_mul(2));
+        let mut tree = new_with_capacity(capacity);
+        for y in self.iter() {
+            tree.insert(y);
+        }
+        self.tree = tree;
+    }
+
+    /// Removes x from the set.
+    ///
+    /// If the set did have x present, true is returned.
+    pub fn remove(&mut self, x: usize) -> bool {
+        x < self.capacity() && self.tree.remove(x)
+    }
+
+    /// Returns the first element in the set, if any.
+    #[must_use]
+    pub fn first(&self) -> Option<usize> {
+        self.tree.first()
+    }
+
+    /// Returns the last element in the set, if any.
+    #[must_use]
+    pub fn last(&self) -> Option<usize> {
+        self.tree.last()
+    }
+
+    /// Returns the smallest element in the
+    /// set that is greater or equal to x, if any.
+    #[must_use]
+    pub fn next(&self, x: usize) -> Option<usize> {
+        if x < self.capacity() {
+            self.tree.next(x)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the biggest element in the
+    /// set that is less or equal to x, if any.
+    #[must_use]
+    pub fn prev(&self, x: usize) -> Option<usize> {
+        self.tree.prev(x.min(self.capacity() - 1))
+    }
+
+    /// Returns an iterator over the values in the set.
+    #[must_use]
+    pub fn iter(&self) -> VEBIterator<'_> {
+        VEBIterator::new(&*self.tree)
+    }
+}
+
+impl FromIterator<usize> for GrowableVEBTree {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for GrowableVEBTree {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
@@ -46,6 +46,12 @@
 //! assert_eq!(tree.next(124), None); // there is no element in te set >= 124
 //! ```
 //!
+//! Trees too big for the stack can be made directly on the heap:
+//! ```
+//! let mut tree = flat_veb::new_boxed::<flat_veb::SizedVEBTree<24>>();
+//! assert_eq!(tree.insert(123), true);
+//! ```
+//!
 //! To get a `VEBTree` with run-time decided capacity:
 //! ```
 //! let mut tree = flat_veb::new_with_capacity(100);
@@ -59,6 +65,9 @@
 //! //tree.insert(128); // panics
 //! ```
 //!
+//! Without an allocator, `VEBTreeEnum` also picks
+//! its capacity at run time, from a few fixed sizes.
+//!
 //!
 //! # Performance
 //!
@@ -83,22 +92,100 @@
 //! # Todo
 //!
 //! - better benchmarks
-//! - reverse iterator
 #![no_std]
 #![warn(missing_docs, missing_debug_implementations)]
 #![warn(clippy::pedantic)]
 
+mod bit_array;
+mod char_set;
+mod offset;
+mod ops;
 mod outer;
+mod saturating;
 mod sizes;
 mod small_set;
+mod veb_enum;
+mod word_scan;
+pub use char_set::CharSet;
+pub use offset::OffsetVEBTree;
+pub use saturating::SaturatingVEBTree;
 pub use sizes::SizedVEBTree;
+pub use veb_enum::VEBTreeEnum;
+
+#[cfg(feature = "serde")]
+mod serialization;
+
+#[cfg(feature = "bytemuck")]
+mod zeroable;
+
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+
+#[cfg(feature = "rkyv")]
+mod archive;
+#[cfg(feature = "rkyv")]
+pub use archive::{ArchivedVEBTree, VEBTreeResolver};
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
 #[cfg(feature = "dyn_capacity")]
 mod dyn_capacity;
 #[cfg(feature = "dyn_capacity")]
 use deep_maybe_uninit::HasDeepMaybeUninit;
 #[cfg(feature = "dyn_capacity")]
-pub use dyn_capacity::{new_with_bits, new_with_capacity};
+pub use dyn_capacity::{
+    new_boxed, new_with_bits, new_with_capacity, try_new_with_capacity, CapacityError,
+};
+#[cfg(feature = "dyn_capacity")]
+mod growable;
+#[cfg(feature = "dyn_capacity")]
+pub use growable::GrowableVEBTree;
+#[cfg(feature = "dyn_capacity")]
+mod map;
+#[cfg(feature = "dyn_capacity")]
+pub use map::VEBMap;
+
+/// Makes a `SizedVEBTree` containing the listed elements, like `vec!`.
+///
+/// With `veb![BITS; x, y, z]`, the tree is a `SizedVEBTree<BITS>`.
+/// Without the size, as in `veb![x, y, z]`, BITS is the smallest
+/// that fits the largest element, which requires the elements
+/// to be constants, since they decide the type.
+///
+/// ```
+/// use flat_veb::{veb, SizedVEBTree, VEBTree};
+///
+/// let tree: SizedVEBTree<24> = veb![24; 1, 2, 3];
+/// assert!(tree.iter().eq([1, 2, 3]));
+///
+/// let tree: SizedVEBTree<7> = veb![5, 100, 64];
+/// assert!(tree.iter().eq([5, 64, 100]));
+/// ```
+#[macro_export]
+macro_rules! veb {
+    ($bits:expr; $($x:expr),* $(,)?) => {
+        <$crate::SizedVEBTree<{ $bits }> as ::core::iter::FromIterator<usize>>::from_iter([$($x),*])
+    };
+    ($($x:expr),* $(,)?) => {
+        $crate::veb![$crate::bits_to_fit(&[$($x),*]); $($x),*]
+    };
+}
+
+/// Returns the smallest number of bits that
+/// fits every element of xs, used by `veb!`.
+#[doc(hidden)]
+#[must_use]
+pub const fn bits_to_fit(xs: &[usize]) -> usize {
+    let mut bits = 0;
+    let mut i = 0;
+    while i < xs.len() {
+        while bits < usize::BITS as usize && xs[i] >> bits != 0 {
+            bits += 1;
+        }
+        i += 1;
+    }
+    bits
+}
 
 mod private {
     pub trait Sealed {}
@@ -114,16 +201,509 @@ mod private {
     impl<T> ConditionalHasDeepMaybeUninit for T {}
 }
 
-/// Constants and implied traits for the `VEBTree` trait,
-/// separated out to make `VEBTree` object safe.
+/// Constants, implied traits and methods taking `Self`
+/// for the `VEBTree` trait, separated out to make `VEBTree` object safe.
 pub trait InnerVEBTree:
-    Copy + Sized + Default + VEBTree + private::ConditionalHasDeepMaybeUninit
+    Copy
+    + Sized
+    + Default
+    + Eq
+    + Send
+    + Sync
+    + 'static
+    + VEBTree
+    + private::ConditionalHasDeepMaybeUninit
 {
     /// The set can hold values with BITS bits.
     const BITS: usize;
 
     /// The set can hold values in [0, CAPACITY)
     const CAPACITY: usize = 1 << Self::BITS;
+
+    /// The number of recursive levels above the base case,
+    /// which is 0 for the types not built from smaller sets.
+    const HEIGHT: usize;
+
+    /// Returns `HEIGHT`, the number of recursive levels.
+    fn height(&self) -> usize {
+        Self::HEIGHT
+    }
+
+    /// Adds X to the set, like `insert(X)`,
+    /// but checks that X is less than `CAPACITY` at compile time.
+    ///
+    /// If the set did not have X present, true is returned.
+    ///
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let mut tree = SizedVEBTree::<8>::new();
+    /// assert!(tree.insert_const::<255>());
+    /// assert!(!tree.insert_const::<255>());
+    /// assert!(tree.contains(255));
+    /// ```
+    ///
+    /// An X that is too big is a compile error,
+    /// when the call is compiled.
+    ///
+    /// ```compile_fail
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree};
+    ///
+    /// let mut tree = SizedVEBTree::<8>::new();
+    /// tree.insert_const::<256>();
+    /// ```
+    fn insert_const<const X: usize>(&mut self) -> bool {
+        const { assert!(X < Self::CAPACITY, "Value out of range for insert_const") };
+        self.insert(X)
+    }
+
+    #[cfg(feature = "dyn_capacity")]
+    /// Initialize the `DeepMaybeUninitialized`
+    /// variant of the struct, making `.assume_init()`
+    /// safe to use after.
+    /// The resulting set will be empty.
+    fn init(value: &mut <Self as HasDeepMaybeUninit>::AsDeepMaybeUninit);
+
+    #[cfg(feature = "alloc")]
+    /// Makes a set with the same elements as a `BTreeSet`.
+    ///
+    /// The elements are inserted in ascending order,
+    /// which is more cache friendly than a random order.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if `set` contains
+    /// a value greater or equal to `CAPACITY`.
+    #[must_use]
+    fn from_btree_set(set: &alloc::collections::BTreeSet<usize>) -> Self {
+        let mut tree = Self::default();
+        for &x in set {
+            debug_assert!(x < Self::CAPACITY, "{x} does not fit in the set.");
+            tree.insert(x);
+        }
+        tree
+    }
+
+    /// Makes a set with the elements of a sorted slice.
+    ///
+    /// This is faster than inserting the elements one by one,
+    /// since the elements of each block are put there together.
+    /// Repeated elements are allowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if `xs` is not sorted,
+    /// or contains a value greater or equal to `CAPACITY`.
+    ///
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let tree = SizedVEBTree::<16>::from_sorted_slice(&[1, 2, 2, 300, 40000]);
+    /// assert!(tree.iter().eq([1, 2, 300, 40000]));
+    /// ```
+    #[must_use]
+    fn from_sorted_slice(xs: &[usize]) -> Self {
+        debug_assert!(
+            xs.windows(2).all(|w| w[0] <= w[1]),
+            "The slice is not sorted."
+        );
+        Self::from_sorted_shifted(xs, 0)
+    }
+
+    /// Like `from_sorted_slice`, but with offset subtracted from
+    /// every element, so blocks of bigger sets can be made
+    /// from parts of the slice without copying them.
+    #[doc(hidden)]
+    #[must_use]
+    fn from_sorted_shifted(xs: &[usize], offset: usize) -> Self;
+
+    /// Makes a set containing i when bit i of
+    /// the concatenated words is set, counting from the
+    /// least significant bit of the first word.
+    ///
+    /// The blocks are made from their parts of the words,
+    /// instead of testing each bit.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if there are not exactly
+    /// `CAPACITY / 64` words, rounded up,
+    /// or if bits after the capacity are set.
+    ///
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let tree = SizedVEBTree::<7>::from_bitmask(&[0b1010, 1]);
+    /// assert!(tree.iter().eq([1, 3, 64]));
+    /// ```
+    #[must_use]
+    fn from_bitmask(words: &[u64]) -> Self;
+
+    /// Sets bit i of the concatenated words for every element i,
+    /// in the layout used by `from_bitmask`.
+    /// The words are expected to be zero beforehand.
+    #[doc(hidden)]
+    fn write_bitmask(&self, words: &mut [u64]);
+
+    /// Adds the elements of block shifted up by offset,
+    /// copying block as one of the `lower` blocks if it has the same type,
+    /// where every element already in the set is smaller than the new ones.
+    ///
+    /// Returns false without changing the set if block
+    /// is bigger than the `lower` blocks, or if this is a base case.
+    #[doc(hidden)]
+    fn put_block<B: InnerVEBTree>(&mut self, offset: usize, block: &B) -> bool {
+        let _ = (offset, block);
+        false
+    }
+
+    /// Adds the elements of the set shifted up by offset to target,
+    /// where every element already in target is smaller than the new ones.
+    ///
+    /// The set is put into target as a block if it can be,
+    /// otherwise the bigger sets are split into their blocks,
+    /// and the base cases are added run by run.
+    #[doc(hidden)]
+    fn put_into<T: InnerVEBTree>(&self, target: &mut T, offset: usize) {
+        if !target.put_block(offset, self) {
+            for (lo, hi) in self.runs() {
+                target.insert_range(offset + lo, offset + hi);
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    /// Returns `CAPACITY / 64` words, rounded up,
+    /// where bit i is set if and only if i is in the set.
+    ///
+    /// This is the inverse of `from_bitmask`.
+    /// Whole blocks are copied as words instead
+    /// of setting the bits one by one.
+    ///
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let tree: SizedVEBTree<7> = [1, 3, 64].into_iter().collect();
+    /// assert_eq!(tree.to_bitmask(), [0b1010, 1]);
+    /// ```
+    #[must_use]
+    fn to_bitmask(&self) -> alloc::vec::Vec<u64> {
+        let mut words = alloc::vec![0; Self::CAPACITY.div_ceil(64)];
+        self.write_bitmask(&mut words);
+        words
+    }
+
+    #[cfg(feature = "bytemuck")]
+    /// Turns a tree with all bytes zero into a valid empty tree.
+    ///
+    /// Use this after allocating a tree with `bytemuck::zeroed_box`.
+    /// A zeroed tree is not empty by itself, because empty trees
+    /// mark their minimum with `usize::MAX`, and not with 0.
+    ///
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let mut tree = bytemuck::zeroed_box::<SizedVEBTree<24>>();
+    /// SizedVEBTree::<24>::from_zeroed(&mut tree);
+    /// assert!(tree.is_empty());
+    /// ```
+    fn from_zeroed(zeroed: &mut Self)
+    where
+        Self: bytemuck::Zeroable,
+    {
+        zeroed.clear();
+    }
+
+    /// Adds every element of other to self.
+    fn union_with(&mut self, other: &Self);
+
+    /// Returns a new set with the elements that are in self or other.
+    #[must_use]
+    fn union(&self, other: &Self) -> Self {
+        let mut result = *self;
+        result.union_with(other);
+        result
+    }
+
+    /// Removes every element from self that is not in other.
+    fn intersect_with(&mut self, other: &Self);
+
+    /// Returns a new set with the elements that are in both self and other.
+    #[must_use]
+    fn intersection(&self, other: &Self) -> Self {
+        let mut result = *self;
+        result.intersect_with(other);
+        result
+    }
+
+    /// Removes every element from self that is in other.
+    fn difference_with(&mut self, other: &Self);
+
+    /// Returns a new set with the elements that are in self but not in other.
+    #[must_use]
+    fn difference(&self, other: &Self) -> Self {
+        let mut result = *self;
+        result.difference_with(other);
+        result
+    }
+
+    /// Keeps the elements that are in exactly one of self and other.
+    fn symmetric_difference_with(&mut self, other: &Self);
+
+    /// Returns a new set with the elements that are in exactly one of self and other.
+    #[must_use]
+    fn symmetric_difference(&self, other: &Self) -> Self {
+        let mut result = *self;
+        result.symmetric_difference_with(other);
+        result
+    }
+
+    /// Returns the elements only in self and
+    /// the elements only in other, as two new sets.
+    ///
+    /// This is the same as `(self.difference(other), other.difference(self))`,
+    /// but both sets are walked once, block by block,
+    /// instead of once per difference.
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let old: SizedVEBTree<16> = [1, 2, 3].into_iter().collect();
+    /// let new: SizedVEBTree<16> = [2, 3, 4].into_iter().collect();
+    /// let (removed, added) = old.diff(&new);
+    /// assert!(removed.iter().eq([1]));
+    /// assert!(added.iter().eq([4]));
+    /// ```
+    #[must_use]
+    fn diff(&self, other: &Self) -> (Self, Self);
+
+    /// Returns a new set with the integers
+    /// in [0, CAPACITY) that are not in self.
+    #[must_use]
+    fn complement(&self) -> Self;
+
+    /// Returns true if every element of self is also in other.
+    fn is_subset(&self, other: &Self) -> bool;
+
+    /// Returns true if every element of other is also in self.
+    fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns true if self and other have no elements in common.
+    fn is_disjoint(&self, other: &Self) -> bool;
+
+    /// Returns the number of elements in both self and other,
+    /// without making the intersection.
+    fn intersection_len(&self, other: &Self) -> usize;
+
+    /// Returns the Jaccard similarity of self and other,
+    /// which is the size of their intersection
+    /// divided by the size of their union.
+    ///
+    /// Two empty sets are equal, so their similarity is 1.
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree};
+    ///
+    /// let a: SizedVEBTree<8> = [1, 2, 3].into_iter().collect();
+    /// let b: SizedVEBTree<8> = [2, 3, 4, 5].into_iter().collect();
+    /// assert_eq!(a.jaccard_similarity(&b), 2.0 / 5.0);
+    /// ```
+    #[allow(clippy::cast_precision_loss)]
+    fn jaccard_similarity(&self, other: &Self) -> f64 {
+        let intersection = self.intersection_len(other);
+        let union = self.len() + other.len() - intersection;
+        if union == 0 {
+            return 1.0;
+        }
+        intersection as f64 / union as f64
+    }
+
+    /// Returns the number of integers in exactly one of self and other,
+    /// which is the length of their symmetric difference.
+    ///
+    /// Like `intersection_len`, it only visits the blocks
+    /// and does not make the symmetric difference.
+    fn hamming_distance(&self, other: &Self) -> usize {
+        self.len() + other.len() - 2 * self.intersection_len(other)
+    }
+
+    /// Removes the elements greater or equal to x from self,
+    /// and returns them in a new set.
+    ///
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let mut a: SizedVEBTree<8> = [1, 2, 3, 17, 41].into_iter().collect();
+    /// let b = a.split_off(3);
+    /// assert!(a.iter().eq([1, 2]));
+    /// assert!(b.iter().eq([3, 17, 41]));
+    /// ```
+    #[must_use]
+    fn split_off(&mut self, x: usize) -> Self;
+
+    /// Moves every element of other into self, leaving other empty.
+    ///
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let mut a: SizedVEBTree<8> = [1, 2, 3].into_iter().collect();
+    /// let mut b: SizedVEBTree<8> = [3, 4, 5].into_iter().collect();
+    /// a.append(&mut b);
+    /// assert!(a.iter().eq([1, 2, 3, 4, 5]));
+    /// assert!(b.is_empty());
+    /// ```
+    fn append(&mut self, other: &mut Self);
+
+    /// Overwrites self to be equal to other,
+    /// reusing the memory self is stored in.
+    ///
+    /// Only the blocks that are non-empty in either set
+    /// are copied, each into the block it replaces,
+    /// so no temporary copy of the whole set is made.
+    /// The base cases are small, and are copied whole.
+    /// `clone_from` does the same.
+    ///
+    /// The sets never own heap memory, so a boxed set
+    /// can be recycled this way without reallocating,
+    /// as long as the `Box` is kept.
+    ///
+    /// ```
+    /// # #[cfg(feature = "dyn_capacity")]
+    /// # {
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let template: SizedVEBTree<16> = [1, 500, 60000].into_iter().collect();
+    /// let mut buffer = flat_veb::new_boxed::<SizedVEBTree<16>>();
+    /// buffer.insert(7);
+    /// buffer.reuse_as(&template);
+    /// assert_eq!(*buffer, template);
+    /// # }
+    /// ```
+    fn reuse_as(&mut self, other: &Self) {
+        *self = *other;
+    }
+
+    /// Returns a new set with offset added to every element,
+    /// or None if any of them would end up outside [0, CAPACITY).
+    ///
+    /// Elements that should be dropped instead can
+    /// be removed with `remove_range` before shifting.
+    ///
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let a: SizedVEBTree<8> = [1, 2, 200].into_iter().collect();
+    /// assert!(a.shift_by(50).unwrap().iter().eq([51, 52, 250]));
+    /// assert!(a.shift_by(-1).unwrap().iter().eq([0, 1, 199]));
+    /// assert_eq!(a.shift_by(-2), None);
+    /// assert_eq!(a.shift_by(56), None);
+    /// ```
+    #[must_use]
+    fn shift_by(&self, offset: isize) -> Option<Self>;
+
+    /// Returns a `SizedVEBTree<M>` with the same elements,
+    /// for moving to a bigger capacity.
+    ///
+    /// The elements are copied block by block,
+    /// in the `lower` blocks of the bigger set whose size matches,
+    /// since the low bits of the elements line up.
+    /// The sizes of the blocks only match for some pairs of sizes,
+    /// for example 15 and 16 bits, or 8 and 16 bits.
+    /// Blocks without a match are added run by run with `insert_range`,
+    /// which still fills whole words instead of descending per element.
+    ///
+    /// ```
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+    ///
+    /// let mut small = SizedVEBTree::<8>::new();
+    /// small.insert_range(10, 200);
+    /// small.insert(255);
+    ///
+    /// let big: SizedVEBTree<16> = small.upgrade_into::<16>();
+    /// assert!(big.iter().eq(small.iter()));
+    /// ```
+    ///
+    /// M has to be at least `BITS`, which is checked at compile time.
+    ///
+    /// ```compile_fail
+    /// use flat_veb::{InnerVEBTree, SizedVEBTree};
+    ///
+    /// let small = SizedVEBTree::<16>::new();
+    /// let _ = small.upgrade_into::<8>();
+    /// ```
+    #[must_use]
+    fn upgrade_into<const M: usize>(&self) -> SizedVEBTree<M>
+    where
+        (): sizes::GetVEBTreeSize<M>,
+    {
+        const {
+            assert!(
+                M >= Self::BITS,
+                "Can not upgrade into a smaller number of bits"
+            );
+        };
+        let mut tree = SizedVEBTree::<M>::default();
+        self.put_into(&mut tree, 0);
+        tree
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Returns a parallel iterator over the values in the set.
+    ///
+    /// Every non-empty block below the top level
+    /// is iterated as an independent task,
+    /// so the values are not produced in order.
+    fn par_iter(&self) -> impl rayon::iter::ParallelIterator<Item = usize> + '_;
+}
+
+/// Returns true if every element of tree
+/// stays in [0, CAPACITY) when shifted by offset.
+pub(crate) fn shift_fits<T: InnerVEBTree>(tree: &T, offset: isize) -> bool {
+    let fits = |x: usize| matches!(x.checked_add_signed(offset), Some(y) if y < T::CAPACITY);
+    match (tree.first(), tree.last()) {
+        (Some(first), Some(last)) => fits(first) && fits(last),
+        _ => true,
+    }
+}
+
+/// Writes the set with runs of consecutive elements collapsed,
+/// like `{1-5, 9, 12-14}`, for the `Display` implementations.
+///
+/// Each run is found with `next` for its start
+/// and `next_gap` for its end.
+pub(crate) fn fmt_runs(tree: &dyn VEBTree, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.write_str("{")?;
+    let mut separator = "";
+    let mut current = tree.first();
+    while let Some(start) = current {
+        f.write_str(separator)?;
+        separator = ", ";
+        let end = tree.next_gap(start).unwrap_or_else(|| tree.capacity());
+        if end - start == 1 {
+            write!(f, "{start}")?;
+        } else {
+            write!(f, "{start}-{}", end - 1)?;
+        }
+        current = if end < tree.capacity() {
+            tree.next(end)
+        } else {
+            None
+        };
+    }
+    f.write_str("}")
+}
+
+/// Shifts every element of tree by offset one at a time,
+/// for when there is no faster way to do it.
+pub(crate) fn shift_by_inserting<T: InnerVEBTree>(tree: &T, offset: isize) -> Option<T> {
+    if !shift_fits(tree, offset) {
+        return None;
+    }
+    let mut result = T::default();
+    for x in tree.iter() {
+        result.insert(x.wrapping_add_signed(offset));
+    }
+    Some(result)
 }
 
 /// Fast implementation of van Emde Boas trees without internal allocation.
@@ -138,24 +718,163 @@ pub trait VEBTree: private::Sealed + core::fmt::Debug {
     /// Trait object version of `VEBTreeWithConstants::CAPACITY`.
     fn capacity(&self) -> usize;
 
-    #[cfg(feature = "dyn_capacity")]
-    /// Initialize the `DeepMaybeUninitialized`
-    /// variant of the struct, making `.assume_init()`
-    /// safe to use after.
-    /// The resulting set will be empty.
-    fn init(value: &mut <Self as HasDeepMaybeUninit>::AsDeepMaybeUninit)
-    where
-        Self: HasDeepMaybeUninit;
+    /// Trait object version of `InnerVEBTree::BITS`,
+    /// so `new_with_bits(tree.bits())` makes an empty set
+    /// of the same type as a boxed tree.
+    fn bits(&self) -> usize;
+
+    /// Returns the number of bytes the set takes up,
+    /// which only depends on the capacity.
+    ///
+    /// This is `size_of` of the concrete type,
+    /// and also works through a trait object.
+    fn memory_bytes(&self) -> usize {
+        core::mem::size_of_val(self)
+    }
+
+    /// Returns the set as `Any`, so a trait object
+    /// can be downcast back to its concrete type.
+    ///
+    /// ```
+    /// # #[cfg(feature = "dyn_capacity")]
+    /// # {
+    /// use flat_veb::{SizedVEBTree, VEBTree};
+    ///
+    /// let tree = flat_veb::new_with_bits(20);
+    /// let sized = tree.as_any().downcast_ref::<SizedVEBTree<20>>();
+    /// assert!(sized.is_some());
+    /// # }
+    /// ```
+    fn as_any(&self) -> &dyn core::any::Any;
+
+    /// Mutable version of `as_any`.
+    fn as_any_mut(&mut self) -> &mut dyn core::any::Any;
+
+    /// Returns the set as a trait object.
+    ///
+    /// The iterators hold a `&dyn VEBTree`, and this lets
+    /// methods like `iter` make them without requiring `Self: Sized`,
+    /// so they are callable on trait objects too.
+    fn as_dyn(&self) -> &dyn VEBTree;
 
     /// Clears the set, removing all elements.
+    ///
+    /// The set never owns heap memory, so this never deallocates,
+    /// and a boxed set stays in the same allocation.
     fn clear(&mut self);
 
+    /// Fills the set, adding every integer in [0, CAPACITY).
+    ///
+    /// Every block is filled directly,
+    /// instead of inserting the elements one by one.
+    fn fill(&mut self);
+
     /// Returns true if the set contains no elements.
     fn is_empty(&self) -> bool;
 
+    /// Returns the number of elements in the set.
+    fn len(&self) -> usize;
+
+    /// Counts the elements in the set without using the stored length,
+    /// by summing the sizes of the non-empty blocks.
+    ///
+    /// This is always equal to `len`, but takes time
+    /// proportional to the number of non-empty blocks,
+    /// which is still much less than iterating the elements.
+    fn count(&self) -> usize;
+
+    /// Returns true if the set contains every integer in [0, CAPACITY).
+    fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Returns the number of elements in the set
+    /// that are strictly smaller than x.
+    ///
+    /// Any x is allowed, and values of at least
+    /// the capacity give the length of the set.
+    ///
+    /// Takes time proportional to the number of
+    /// non-empty blocks below x at each level,
+    /// which is O(√U) in the worst case,
+    /// not O(log log U) like the other queries.
+    fn rank(&self, x: usize) -> usize;
+
+    /// Returns the k-th smallest element in the set,
+    /// counting from 0, if there are more than k elements.
+    ///
+    /// This is the inverse of `rank`,
+    /// so `select(rank(x)) == Some(x)` for x in the set.
+    /// Like `rank`, it takes time proportional to the number
+    /// of non-empty blocks it skips at each level,
+    /// which is O(√U) in the worst case.
+    ///
+    /// ```
+    /// use flat_veb::{SizedVEBTree, VEBTree};
+    ///
+    /// let tree: SizedVEBTree<8> = [3, 50, 200].into_iter().collect();
+    /// assert_eq!(tree.select(1), Some(50));
+    /// assert_eq!(tree.select(3), None);
+    /// ```
+    fn select(&self, k: usize) -> Option<usize>;
+
+    /// Returns the number of elements in the set
+    /// that are in the range [lo, hi).
+    ///
+    /// hi is clamped to the capacity,
+    /// and the count is 0 if lo >= hi.
+    ///
+    /// This is two calls to `rank`, so it is O(√U) in the worst case.
+    fn count_range(&self, lo: usize, hi: usize) -> usize {
+        let hi = hi.min(self.capacity());
+        if lo >= hi {
+            return 0;
+        }
+        self.rank(hi) - self.rank(lo)
+    }
+
+    /// Returns the number of elements less than x, which is `rank(x)`.
+    ///
+    /// Like the other counts below, any x is allowed.
+    /// Each of them is a call to `rank`,
+    /// so they are O(√U) in the worst case, not O(log log U).
+    fn count_lt(&self, x: usize) -> usize {
+        self.rank(x)
+    }
+
+    /// Returns the number of elements less or equal to x.
+    fn count_le(&self, x: usize) -> usize {
+        self.rank(x.saturating_add(1))
+    }
+
+    /// Returns the number of elements greater than x.
+    fn count_gt(&self, x: usize) -> usize {
+        self.len() - self.count_le(x)
+    }
+
+    /// Returns the number of elements greater or equal to x.
+    fn count_ge(&self, x: usize) -> usize {
+        self.len() - self.count_lt(x)
+    }
+
     /// Returns true if the set contains x.
+    ///
+    /// x should be less than the capacity,
+    /// which is debug asserted.
+    /// In release builds, bigger x give false.
     fn contains(&self, x: usize) -> bool;
 
+    /// Like `contains`, but returns an error instead of
+    /// debug asserting if x is not less than the capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns `OutOfBounds` if x is at least the capacity.
+    fn checked_contains(&self, x: usize) -> Result<bool, OutOfBounds> {
+        OutOfBounds::check(x, self.capacity())?;
+        Ok(self.contains(x))
+    }
+
     /// Adds x to the set.
     ///
     /// If the set did not have x present, true is returned.
@@ -164,71 +883,1152 @@ pub trait VEBTree: private::Sealed + core::fmt::Debug {
     /// and the entry is not updated.
     fn insert(&mut self, x: usize) -> bool;
 
+    /// Like `insert`, but returns an error instead of
+    /// debug asserting if x is not less than the capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns `OutOfBounds` if x is at least the capacity,
+    /// leaving the set unchanged.
+    fn checked_insert(&mut self, x: usize) -> Result<bool, OutOfBounds> {
+        OutOfBounds::check(x, self.capacity())?;
+        Ok(self.insert(x))
+    }
+
+    /// Inserts x like `insert`, and also returns
+    /// the smallest element greater than x, if any.
+    ///
+    /// The successor is found on the way down
+    /// while inserting, which is faster than
+    /// calling `insert` and `next_excl` separately.
+    /// ```
+    /// use flat_veb::VEBTree;
+    ///
+    /// let mut tree = flat_veb::SizedVEBTree::<16>::new();
+    /// tree.insert(10);
+    /// assert_eq!(tree.insert_and_return_neighbor(5), (true, Some(10)));
+    /// assert_eq!(tree.insert_and_return_neighbor(5), (false, Some(10)));
+    /// assert_eq!(tree.insert_and_return_neighbor(20), (true, None));
+    /// ```
+    fn insert_and_return_neighbor(&mut self, x: usize) -> (bool, Option<usize>);
+
+    /// Adds every integer in the range [lo, hi) to the set.
+    ///
+    /// hi is clamped to the capacity,
+    /// and nothing is added if lo >= hi.
+    /// Blocks covered by the range are filled
+    /// without inserting the elements one by one.
+    fn insert_range(&mut self, lo: usize, hi: usize);
+
     /// If the set contains x,
     /// removes it from the set.
     /// Returns whether such an element was present.
     fn remove(&mut self, x: usize) -> bool;
 
+    /// Removes x if it is in the set, and inserts it otherwise.
+    /// Returns true if x is in the set afterwards.
+    fn toggle(&mut self, x: usize) -> bool;
+
+    /// If the set contains old, removes it and inserts new.
+    /// Returns whether old was present.
+    ///
+    /// Nothing is inserted if old was not present.
+    /// If new was already present, the set just loses old.
+    /// ```
+    /// use flat_veb::VEBTree;
+    ///
+    /// let mut tree = flat_veb::SizedVEBTree::<8>::new();
+    /// tree.insert(3);
+    /// assert!(tree.replace(3, 7));
+    /// assert!(!tree.replace(3, 9));
+    /// assert!(tree.iter().eq([7]));
+    /// ```
+    fn replace(&mut self, old: usize, new: usize) -> bool {
+        if old == new {
+            return self.contains(old);
+        }
+        if !self.remove(old) {
+            return false;
+        }
+        self.insert(new);
+        true
+    }
+
+    /// Toggles every integer in the range [lo, hi),
+    /// removing the ones in the set and inserting the rest.
+    ///
+    /// hi is clamped to the capacity,
+    /// and nothing is changed if lo >= hi.
+    /// Blocks covered by the range are complemented
+    /// without toggling the elements one by one.
+    fn toggle_range(&mut self, lo: usize, hi: usize);
+
+    /// Removes every integer in the range [lo, hi) from the set.
+    ///
+    /// hi is clamped to the capacity,
+    /// and nothing is removed if lo >= hi.
+    /// Blocks covered by the range are cleared
+    /// without removing the elements one by one.
+    fn remove_range(&mut self, lo: usize, hi: usize);
+
+    /// Clears the range [lo, hi), leaving no element of the set in it.
+    ///
+    /// This is the same as `remove_range`, which already
+    /// resets covered blocks as a whole and only visits
+    /// the non-empty blocks, and updates the minimum and maximum
+    /// if they were in the range.
+    fn clear_range(&mut self, lo: usize, hi: usize) {
+        self.remove_range(lo, hi);
+    }
+
+    /// Removes the first element from the set and returns it, if any.
+    /// The first element is always the minimum element in the set.
+    fn pop_first(&mut self) -> Option<usize>;
+
+    /// Removes the last element from the set and returns it, if any.
+    /// The last element is always the maximum element in the set.
+    fn pop_last(&mut self) -> Option<usize>;
+
     /// Returns the first element in the set that is
     /// greater or equal to x, if any.
+    ///
+    /// x should be less than the capacity,
+    /// which is debug asserted.
+    /// In release builds, bigger x give None.
     fn next(&self, x: usize) -> Option<usize>;
 
+    /// Like `next`, but returns an error instead of
+    /// debug asserting if x is not less than the capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns `OutOfBounds` if x is at least the capacity.
+    fn checked_next(&self, x: usize) -> Result<Option<usize>, OutOfBounds> {
+        OutOfBounds::check(x, self.capacity())?;
+        Ok(self.next(x))
+    }
+
+    /// Returns the first element in the set that is
+    /// strictly greater than x, if any.
+    ///
+    /// Any x is allowed, and there is no such element
+    /// if x is at least `CAPACITY - 1`, which includes `usize::MAX`.
+    fn next_excl(&self, x: usize) -> Option<usize> {
+        match x.checked_add(1) {
+            Some(y) if y < self.capacity() => self.next(y),
+            _ => None,
+        }
+    }
+
     /// Returns the last element in the set that is
     /// smaller or equal to x, if any.
+    ///
+    /// x should be less than the capacity,
+    /// which is debug asserted.
+    /// In release builds, bigger x give None.
     fn prev(&self, x: usize) -> Option<usize>;
 
-    /// Returns the first element in the set, if any.
-    /// This element is always the minimum of all elements in the set.
-    fn first(&self) -> Option<usize>;
-
-    /// Returns the last element in the set, if any.
-    /// This element is always the maximum of all elements in the set.
-    fn last(&self) -> Option<usize>;
+    /// Like `prev`, but returns an error instead of
+    /// debug asserting if x is not less than the capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns `OutOfBounds` if x is at least the capacity.
+    fn checked_prev(&self, x: usize) -> Result<Option<usize>, OutOfBounds> {
+        OutOfBounds::check(x, self.capacity())?;
+        Ok(self.prev(x))
+    }
 
-    /// Returns an iterator over the values in the set.
-    fn iter(&self) -> VEBIterator<'_>
-    where
-        Self: Sized,
-    {
-        VEBIterator {
-            tree: self,
-            next_start: 0,
-            prev_end: self.capacity(),
+    /// Returns the last element in the set that is
+    /// strictly smaller than x, if any.
+    ///
+    /// Any x is allowed, so there is no such element if x is 0,
+    /// and x above the capacity acts like the capacity.
+    fn prev_excl(&self, x: usize) -> Option<usize> {
+        if x == 0 {
+            return None;
         }
+        self.prev((x - 1).min(self.capacity() - 1))
     }
-}
 
-/// This struct is created by the iter method
+    /// Returns the element in the set nearest to x, if any.
+    ///
+    /// This is x itself if it is in the set,
+    /// and ties are broken toward the smaller element.
+    fn closest(&self, x: usize) -> Option<usize> {
+        match (self.prev(x), self.next(x)) {
+            (Some(a), Some(b)) => Some(if x - a <= b - x { a } else { b }),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Returns the first integer that is greater or equal
+    /// to x and not in the set, if any.
+    ///
+    /// Runs of full blocks are skipped one block at a time,
+    /// so this is slower than `next` on dense sets.
+    fn next_gap(&self, x: usize) -> Option<usize>;
+
+    /// Returns true if every integer in the range [lo, hi) is in the set.
+    ///
+    /// This is true for an empty range,
+    /// and false if hi is greater than the capacity,
+    /// since the set can't contain those integers.
+    /// Stops at the first missing integer.
+    fn contains_range(&self, lo: usize, hi: usize) -> bool {
+        if lo >= hi {
+            return true;
+        }
+        if hi > self.capacity() {
+            return false;
+        }
+        !matches!(self.next_gap(lo), Some(gap) if gap < hi)
+    }
+
+    /// Returns the smallest non-negative integer not in the set,
+    /// or the capacity if the set is full.
+    fn mex(&self) -> usize {
+        self.next_gap(0).unwrap_or_else(|| self.capacity())
+    }
+
+    /// Returns the number of integers in [0, CAPACITY)
+    /// that are not in the set.
+    fn count_gaps(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    /// Returns the number of maximal runs of consecutive integers
+    /// in the set, which is the number of items `runs` yields.
+    ///
+    /// Jumps from run to run with `next_gap` and `next`,
+    /// so it takes O(log log U) time per run.
+    ///
+    /// ```
+    /// use flat_veb::{SizedVEBTree, VEBTree};
+    ///
+    /// let tree: SizedVEBTree<4> = [0, 1, 2, 5, 6, 9].into_iter().collect();
+    /// assert_eq!(tree.count_runs(), 3);
+    /// ```
+    fn count_runs(&self) -> usize {
+        let mut runs = 0;
+        let mut start = self.first();
+        while let Some(x) = start {
+            runs += 1;
+            start = self.next_gap(x).and_then(|gap| self.next(gap));
+        }
+        runs
+    }
+
+    /// Returns the start and length of the longest run
+    /// of consecutive integers in the set, or None if it is empty.
+    ///
+    /// If several runs are the longest, the first one is returned.
+    ///
+    /// ```
+    /// use flat_veb::{SizedVEBTree, VEBTree};
+    ///
+    /// let tree: SizedVEBTree<4> = [1, 2, 5, 6, 7, 10, 11, 12].into_iter().collect();
+    /// assert_eq!(tree.longest_run(), Some((5, 3)));
+    /// ```
+    fn longest_run(&self) -> Option<(usize, usize)> {
+        let capacity = self.capacity();
+        let (mut longest_start, mut longest_len) = (0, 0);
+        let mut start = self.first();
+        while let Some(x) = start {
+            let end = self.next_gap(x).unwrap_or(capacity);
+            if end - x > longest_len {
+                (longest_start, longest_len) = (x, end - x);
+            }
+            start = if end < capacity { self.next(end) } else { None };
+        }
+        (longest_len > 0).then_some((longest_start, longest_len))
+    }
+
+    /// Returns the start of the first run of at least n
+    /// consecutive integers in [0, CAPACITY) that are not in the set,
+    /// or None if there is no such run.
+    ///
+    /// An empty run fits anywhere, so this is `Some(0)` if n is 0.
+    ///
+    /// ```
+    /// use flat_veb::{SizedVEBTree, VEBTree};
+    ///
+    /// let tree: SizedVEBTree<4> = [0, 3, 4, 8].into_iter().collect();
+    /// assert_eq!(tree.first_free_run(2), Some(1));
+    /// assert_eq!(tree.first_free_run(3), Some(5));
+    /// assert_eq!(tree.first_free_run(7), Some(9));
+    /// assert_eq!(tree.first_free_run(8), None);
+    /// ```
+    fn first_free_run(&self, n: usize) -> Option<usize> {
+        if n == 0 {
+            return Some(0);
+        }
+        let capacity = self.capacity();
+        let mut gap = self.next_gap(0);
+        while let Some(start) = gap {
+            let end = self.next(start).unwrap_or(capacity);
+            if end - start >= n {
+                return Some(start);
+            }
+            if end == capacity {
+                return None;
+            }
+            gap = self.next_gap(end);
+        }
+        None
+    }
+
+    /// Returns the k-th smallest integer in [0, CAPACITY)
+    /// that is not in the set, counting from 0, if any.
+    ///
+    /// There are `x - rank(x)` such integers below x,
+    /// so the position is found with a binary search using `rank`,
+    /// which makes O(log U) calls to it.
+    /// With `rank` being O(√U), that is O(√U log U) in the worst case,
+    /// so for many gaps in order, `gaps` is faster.
+    fn nth_gap(&self, k: usize) -> Option<usize> {
+        if k >= self.count_gaps() {
+            return None;
+        }
+
+        // The answer is the smallest x with more than k gaps in [0, x],
+        // and at most len elements can be skipped before it.
+        let mut lo = k;
+        let mut hi = (k + self.len()).min(self.capacity() - 1);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if mid + 1 - self.rank(mid + 1) > k {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        Some(lo)
+    }
+
+    /// Returns the first element in the set, if any.
+    /// This element is always the minimum of all elements in the set.
+    fn first(&self) -> Option<usize>;
+
+    /// Returns the last element in the set, if any.
+    /// This element is always the maximum of all elements in the set.
+    fn last(&self) -> Option<usize>;
+
+    /// Returns an iterator over the values in the set.
+    ///
+    /// References to `SizedVEBTree`s can also be iterated directly:
+    /// ```
+    /// let mut tree = flat_veb::SizedVEBTree::<8>::new();
+    /// tree.insert(42);
+    /// tree.insert(7);
+    ///
+    /// let mut values = Vec::new();
+    /// for x in &tree {
+    ///     values.push(x);
+    /// }
+    /// assert_eq!(values, [7, 42]);
+    /// ```
+    ///
+    /// It is also callable on trait objects:
+    /// ```
+    /// use flat_veb::VEBTree;
+    ///
+    /// let mut tree = flat_veb::new_with_capacity(100);
+    /// tree.insert(42);
+    /// assert!(tree.iter().eq([42]));
+    /// ```
+    fn iter(&self) -> VEBIterator<'_> {
+        VEBIterator::new(self.as_dyn())
+    }
+
+    #[cfg(feature = "alloc")]
+    /// Returns a `BTreeSet` with the same elements as the set.
+    fn to_btree_set(&self) -> alloc::collections::BTreeSet<usize>
+    where
+        Self: Sized,
+    {
+        self.iter().collect()
+    }
+
+    #[cfg(feature = "alloc")]
+    /// Returns a `Vec` with the elements of the set in ascending order.
+    ///
+    /// The vector is allocated with room for exactly `len` elements
+    /// before it is filled, so it is never reallocated.
+    fn to_sorted_vec(&self) -> alloc::vec::Vec<usize> {
+        let mut values = alloc::vec::Vec::with_capacity(self.len());
+        let mut current = self.first();
+        while let Some(x) = current {
+            values.push(x);
+            current = self.next_excl(x);
+        }
+        values
+    }
+
+    /// Writes the elements of the set in ascending order
+    /// to the start of out, and returns how many were written.
+    ///
+    /// If out is shorter than `len`, only the
+    /// `out.len()` smallest elements are written.
+    /// The rest of out is left unchanged.
+    /// ```
+    /// use flat_veb::VEBTree;
+    ///
+    /// let tree: flat_veb::SizedVEBTree<8> = [5, 1, 9].into_iter().collect();
+    /// let mut out = [0; 2];
+    /// assert_eq!(tree.copy_to_slice(&mut out), 2);
+    /// assert_eq!(out, [1, 5]);
+    /// ```
+    fn copy_to_slice(&self, out: &mut [usize]) -> usize {
+        let mut written = 0;
+        let mut current = self.first();
+        while let Some(x) = current {
+            let Some(slot) = out.get_mut(written) else {
+                break;
+            };
+            *slot = x;
+            written += 1;
+            current = self.next_excl(x);
+        }
+        written
+    }
+
+    /// Returns an iterator over the values in the set
+    /// that are greater or equal to x.
+    fn iter_from(&self, x: usize) -> VEBIterator<'_> {
+        VEBIterator::starting_at(self.as_dyn(), x)
+    }
+
+    /// Returns an iterator over the values in the set
+    /// that are in the range [lo, hi).
+    ///
+    /// hi is clamped to the capacity,
+    /// and the range is empty if lo >= hi.
+    fn range(&self, lo: usize, hi: usize) -> VEBRangeIterator<'_> {
+        VEBRangeIterator::new(self.as_dyn(), lo, hi)
+    }
+
+    /// Returns an iterator over the integers
+    /// in [0, CAPACITY) that are not in the set.
+    ///
+    /// ```
+    /// use flat_veb::VEBTree;
+    ///
+    /// let mut tree = flat_veb::SizedVEBTree::<4>::new();
+    /// tree.insert_range(2, 14);
+    /// tree.insert(15);
+    /// assert!(tree.gaps().eq([0, 1, 14]));
+    /// ```
+    fn gaps(&self) -> GapIterator<'_> {
+        GapIterator::new(self.as_dyn())
+    }
+
+    /// Returns an iterator over the maximal runs of
+    /// consecutive integers in the set, in ascending order,
+    /// as half-open ranges `(start, end)`.
+    ///
+    /// ```
+    /// use flat_veb::VEBTree;
+    ///
+    /// let mut tree = flat_veb::SizedVEBTree::<4>::new();
+    /// tree.insert_range(2, 5);
+    /// tree.insert(9);
+    /// tree.insert(15);
+    /// assert!(tree.runs().eq([(2, 5), (9, 10), (15, 16)]));
+    /// ```
+    fn runs(&self) -> RunIterator<'_> {
+        RunIterator::new(self.as_dyn())
+    }
+
+    /// Returns an iterator over the pairs of consecutive elements,
+    /// yielding `(x, y)` for every element x with a successor y.
+    ///
+    /// ```
+    /// use flat_veb::{SizedVEBTree, VEBTree};
+    ///
+    /// let tree: SizedVEBTree<8> = [3, 7, 8, 20].into_iter().collect();
+    /// assert!(tree.pairs().eq([(3, 7), (7, 8), (8, 20)]));
+    /// assert!(tree.pairs().map(|(x, y)| y - x).eq([4, 1, 12]));
+    /// ```
+    fn pairs(&self) -> PairIterator<'_> {
+        PairIterator::new(self.as_dyn())
+    }
+
+    /// Keeps only the elements x for which f(x) returns true,
+    /// visiting the elements in ascending order.
+    fn retain<F: FnMut(usize) -> bool>(&mut self, mut f: F)
+    where
+        Self: Sized,
+    {
+        let mut current = self.first();
+        while let Some(x) = current {
+            if !f(x) {
+                self.remove(x);
+            }
+            current = self.next_excl(x);
+        }
+    }
+
+    /// Returns an iterator removing the values in the set in ascending order.
+    ///
+    /// The set is empty after the iterator is dropped,
+    /// even if not all values were consumed.
+    fn drain(&mut self) -> Drain<'_>
+    where
+        Self: Sized,
+    {
+        Drain { tree: self }
+    }
+
+    /// Checks whether x is in the set, returning an `Entry`
+    /// for inserting or removing it depending on the answer.
+    ///
+    /// The lookup is an `insert` of x, so getting the entry
+    /// and then inserting through it is one descent of the tree.
+    /// Removing through an occupied entry, or dropping a vacant
+    /// entry without inserting, costs one `remove` on top of that.
+    ///
+    /// ```
+    /// use flat_veb::{Entry, VEBTree};
+    ///
+    /// let mut tree = flat_veb::SizedVEBTree::<8>::new();
+    /// tree.insert(3);
+    ///
+    /// match tree.entry(3) {
+    ///     Entry::Occupied(entry) => assert_eq!(entry.remove(), 3),
+    ///     Entry::Vacant(_) => unreachable!(),
+    /// }
+    /// tree.entry(5).or_insert();
+    /// assert!(tree.iter().eq([5]));
+    /// ```
+    fn entry(&mut self, x: usize) -> Entry<'_>
+    where
+        Self: Sized,
+    {
+        Entry::new(self, x)
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'a (dyn VEBTree + 'b) {
+    type Item = usize;
+    type IntoIter = VEBIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        VEBIterator::new(self)
+    }
+}
+
+/// The error returned by the checked methods
+/// on objects implementing `VEBTree`
+/// when given a value the set can't hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds {
+    /// The value that was out of bounds.
+    pub value: usize,
+    /// The capacity of the set, which the value was not less than.
+    pub capacity: usize,
+}
+
+impl OutOfBounds {
+    fn check(value: usize, capacity: usize) -> Result<(), Self> {
+        if value < capacity {
+            Ok(())
+        } else {
+            Err(Self { value, capacity })
+        }
+    }
+}
+
+impl core::fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Value out of bounds: {}. The capacity is {}.",
+            self.value, self.capacity
+        )
+    }
+}
+
+/// This struct is created by the iter method
 /// on objects implementing `VEBTree`.
+///
+/// It can be iterated from both ends,
+/// and the two ends never yield the same element.
 #[derive(Debug)]
 pub struct VEBIterator<'a> {
+    range: VEBRangeIterator<'a>,
+    /// The number of values left, if it has been counted.
+    remaining: core::cell::Cell<Option<usize>>,
+}
+
+impl<'a> VEBIterator<'a> {
+    pub(crate) fn new(tree: &'a dyn VEBTree) -> Self {
+        Self {
+            range: VEBRangeIterator::new(tree, 0, tree.capacity()),
+            remaining: Some(tree.len()).into(),
+        }
+    }
+
+    /// Makes an iterator over the values in tree
+    /// that are greater or equal to start.
+    ///
+    /// The values are only counted with `rank` the first time
+    /// the length is asked for, since that is O(√U) in the worst case,
+    /// so iterating without asking for it costs nothing extra.
+    pub fn starting_at(tree: &'a dyn VEBTree, start: usize) -> Self {
+        Self {
+            range: VEBRangeIterator::new(tree, start, tree.capacity()),
+            remaining: None.into(),
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        if let Some(remaining) = self.remaining.get() {
+            return remaining;
+        }
+        let remaining = self
+            .range
+            .tree
+            .count_range(self.range.next_start, self.range.prev_end);
+        self.remaining.set(Some(remaining));
+        remaining
+    }
+}
+
+impl<'a> Iterator for VEBIterator<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.range.next()?;
+        if let Some(remaining) = self.remaining.get_mut() {
+            *remaining -= 1;
+        }
+        Some(value)
+    }
+
+    /// Skips like `VEBRangeIterator::nth`.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let value = self.range.nth(n);
+        if let Some(remaining) = self.remaining.get_mut() {
+            *remaining = match value {
+                Some(_) => *remaining - n - 1,
+                None => 0,
+            };
+        }
+        value
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for VEBIterator<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.range.next_back()?;
+        if let Some(remaining) = self.remaining.get_mut() {
+            *remaining -= 1;
+        }
+        Some(value)
+    }
+}
+
+impl<'a> ExactSizeIterator for VEBIterator<'a> {}
+
+/// Below this many skipped elements, `nth` on the iterators
+/// steps with `next` instead of using `rank` and `select`.
+const NTH_BY_RANK: usize = 64;
+
+/// This struct is created by the range method
+/// on objects implementing `VEBTree`.
+///
+/// It can be iterated from both ends,
+/// and the two ends never yield the same element.
+#[derive(Debug)]
+pub struct VEBRangeIterator<'a> {
     tree: &'a dyn VEBTree,
     next_start: usize,
     prev_end: usize,
 }
 
-impl<'a> Iterator for VEBIterator<'a> {
+impl<'a> VEBRangeIterator<'a> {
+    pub(crate) fn new(tree: &'a dyn VEBTree, lo: usize, hi: usize) -> Self {
+        let prev_end = hi.min(tree.capacity());
+        Self {
+            tree,
+            next_start: lo.min(prev_end),
+            prev_end,
+        }
+    }
+}
+
+impl<'a> Iterator for VEBRangeIterator<'a> {
     type Item = usize;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.next_start == self.tree.capacity() {
-            None
-        } else {
-            let value = self.tree.next(self.next_start)?;
-            self.next_start = value + 1;
-            Some(value)
+        if self.next_start >= self.prev_end {
+            return None;
+        }
+
+        let value = self.tree.next(self.next_start)?;
+        if value >= self.prev_end {
+            self.next_start = self.prev_end;
+            return None;
+        }
+
+        // value < prev_end <= capacity, so this can't overflow,
+        // and next_start == prev_end stops the iterator
+        // after the last element, even if it is capacity - 1.
+        self.next_start = value + 1;
+        Some(value)
+    }
+
+    /// Steps with `next` when n is less than `NTH_BY_RANK`,
+    /// and otherwise skips n elements with one `rank` and one `select`.
+    /// Those take O(√U) time in the worst case,
+    /// so they are only worth it when there are many steps to skip.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n < NTH_BY_RANK {
+            for _ in 0..n {
+                self.next()?;
+            }
+            return self.next();
+        }
+        if self.next_start >= self.prev_end {
+            return None;
+        }
+
+        let k = self.tree.rank(self.next_start).saturating_add(n);
+        match self.tree.select(k) {
+            Some(value) if value < self.prev_end => {
+                self.next_start = value + 1;
+                Some(value)
+            }
+            _ => {
+                self.next_start = self.prev_end;
+                None
+            }
         }
     }
 }
 
-impl<'a> DoubleEndedIterator for VEBIterator<'a> {
+impl<'a> DoubleEndedIterator for VEBRangeIterator<'a> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        if self.prev_end == 0 {
-            None
+        if self.prev_end <= self.next_start {
+            return None;
+        }
+
+        let value = self.tree.prev(self.prev_end - 1)?;
+        if value < self.next_start {
+            self.prev_end = self.next_start;
+            return None;
+        }
+
+        self.prev_end = value;
+        Some(value)
+    }
+}
+
+/// This struct is created by the gaps method
+/// on objects implementing `VEBTree`.
+///
+/// Runs of elements in the set are skipped with `next_gap`,
+/// and the gaps are yielded until the next element after them.
+#[derive(Debug)]
+pub struct GapIterator<'a> {
+    tree: &'a dyn VEBTree,
+    next_start: usize,
+    run_end: usize,
+    /// The number of gaps left, counted from the length of the set,
+    /// so it never needs `rank`.
+    remaining: usize,
+}
+
+impl<'a> GapIterator<'a> {
+    pub(crate) fn new(tree: &'a dyn VEBTree) -> Self {
+        Self {
+            tree,
+            next_start: 0,
+            run_end: 0,
+            remaining: tree.capacity() - tree.len(),
+        }
+    }
+}
+
+impl<'a> Iterator for GapIterator<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_start == self.run_end {
+            let capacity = self.tree.capacity();
+            if self.next_start >= capacity {
+                return None;
+            }
+            let Some(gap) = self.tree.next_gap(self.next_start) else {
+                self.next_start = capacity;
+                self.run_end = capacity;
+                return None;
+            };
+            self.next_start = gap;
+            self.run_end = self.tree.next(gap).unwrap_or(capacity);
+        }
+
+        let value = self.next_start;
+        self.next_start += 1;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for GapIterator<'a> {}
+
+/// This struct is created by the runs method
+/// on objects implementing `VEBTree`.
+///
+/// Each run starts at the next element with `next`,
+/// and ends at the first gap after it with `next_gap`,
+/// so the elements inside a run are never visited.
+#[derive(Debug)]
+pub struct RunIterator<'a> {
+    tree: &'a dyn VEBTree,
+    next_start: usize,
+}
+
+impl<'a> RunIterator<'a> {
+    pub(crate) fn new(tree: &'a dyn VEBTree) -> Self {
+        Self {
+            tree,
+            next_start: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for RunIterator<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let capacity = self.tree.capacity();
+        if self.next_start >= capacity {
+            return None;
+        }
+        let Some(start) = self.tree.next(self.next_start) else {
+            self.next_start = capacity;
+            return None;
+        };
+        let end = self.tree.next_gap(start).unwrap_or(capacity);
+        self.next_start = end;
+        Some((start, end))
+    }
+}
+
+/// This struct is created by the pairs method
+/// on objects implementing `VEBTree`.
+///
+/// The successor of the last yielded pair is kept,
+/// so every element is only looked up once.
+#[derive(Debug)]
+pub struct PairIterator<'a> {
+    iter: VEBIterator<'a>,
+    current: Option<usize>,
+}
+
+impl<'a> PairIterator<'a> {
+    pub(crate) fn new(tree: &'a dyn VEBTree) -> Self {
+        let mut iter = VEBIterator::new(tree);
+        let current = iter.next();
+        Self { iter, current }
+    }
+}
+
+impl<'a> Iterator for PairIterator<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.current?;
+        let y = self.iter.next();
+        self.current = y;
+        Some((x, y?))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.iter.len();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for PairIterator<'a> {}
+
+/// This struct is created by the drain method
+/// on objects implementing `VEBTree`.
+///
+/// Dropping it clears the set.
+#[derive(Debug)]
+pub struct Drain<'a> {
+    tree: &'a mut dyn VEBTree,
+}
+
+impl<'a> Iterator for Drain<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tree.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.tree.len();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Drain<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.tree.pop_last()
+    }
+}
+
+impl<'a> ExactSizeIterator for Drain<'a> {}
+
+impl<'a> Drop for Drain<'a> {
+    fn drop(&mut self) {
+        if !self.tree.is_empty() {
+            self.tree.clear();
+        }
+    }
+}
+
+/// This struct is created by the `into_iter` method
+/// on `SizedVEBTree`s, iterating over a tree by value.
+///
+/// Since the trees are `Copy`, `for x in tree` iterates
+/// over a copy, and leaves `tree` unchanged and usable.
+/// The copy is moved into the iterator,
+/// so iterate over a reference instead for big trees.
+/// ```
+/// use flat_veb::{SizedVEBTree, VEBTree};
+///
+/// let tree: SizedVEBTree<8> = [3, 1, 2].into_iter().collect();
+/// let values: Vec<usize> = tree.into_iter().collect();
+/// assert_eq!(values, [1, 2, 3]);
+/// assert_eq!(tree.len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IntoIter<T: InnerVEBTree> {
+    tree: T,
+}
+
+impl<T: InnerVEBTree> IntoIter<T> {
+    pub(crate) fn new(tree: T) -> Self {
+        Self { tree }
+    }
+}
+
+impl<T: InnerVEBTree> Iterator for IntoIter<T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tree.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.tree.len();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: InnerVEBTree> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.tree.pop_last()
+    }
+}
+
+impl<T: InnerVEBTree> ExactSizeIterator for IntoIter<T> {}
+
+/// A value in a set, which may or may not be in it.
+///
+/// This enum is created by the entry method
+/// on objects implementing `VEBTree`.
+#[derive(Debug)]
+pub enum Entry<'a> {
+    /// The value is in the set.
+    Occupied(OccupiedEntry<'a>),
+    /// The value is not in the set.
+    Vacant(VacantEntry<'a>),
+}
+
+/// The operations an entry needs from its set.
+trait EntrySet: core::fmt::Debug {
+    fn insert(&mut self, x: usize) -> bool;
+    fn remove(&mut self, x: usize) -> bool;
+}
+
+impl<T: VEBTree + ?Sized> EntrySet for T {
+    fn insert(&mut self, x: usize) -> bool {
+        VEBTree::insert(self, x)
+    }
+
+    fn remove(&mut self, x: usize) -> bool {
+        VEBTree::remove(self, x)
+    }
+}
+
+impl<'a> Entry<'a> {
+    /// Inserts the value right away, so the entry never has to look
+    /// it up again unless it is removed or not kept.
+    fn new(tree: &'a mut dyn EntrySet, value: usize) -> Self {
+        if tree.insert(value) {
+            Self::Vacant(VacantEntry { tree, value })
         } else {
-            let value = self.tree.prev(self.prev_end - 1)?;
-            self.prev_end = value;
-            Some(value)
+            Self::Occupied(OccupiedEntry { tree, value })
         }
     }
+
+    /// Returns the value the entry is for.
+    #[must_use]
+    pub fn get(&self) -> usize {
+        match self {
+            Self::Occupied(entry) => entry.get(),
+            Self::Vacant(entry) => entry.get(),
+        }
+    }
+
+    /// Returns true if the value is in the set.
+    #[must_use]
+    pub fn is_occupied(&self) -> bool {
+        matches!(self, Self::Occupied(_))
+    }
+
+    /// Inserts the value if it is not in the set.
+    pub fn or_insert(self) {
+        if let Self::Vacant(entry) = self {
+            entry.insert();
+        }
+    }
+}
+
+/// An entry for a value in the set.
+/// It is part of the `Entry` enum.
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    tree: &'a mut dyn EntrySet,
+    value: usize,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Returns the value the entry is for.
+    #[must_use]
+    pub fn get(&self) -> usize {
+        self.value
+    }
+
+    /// Removes the value from the set, returning it.
+    ///
+    /// This is a call to `remove` on the set.
+    #[allow(clippy::must_use_candidate)]
+    pub fn remove(self) -> usize {
+        let removed = self.tree.remove(self.value);
+        debug_assert!(removed, "an occupied entry's value is in the set");
+        self.value
+    }
+}
+
+/// An entry for a value not in the set.
+/// It is part of the `Entry` enum.
+///
+/// The value is already in the set while the entry is alive,
+/// and is removed again if the entry is dropped without `insert`.
+#[derive(Debug)]
+pub struct VacantEntry<'a> {
+    tree: &'a mut dyn EntrySet,
+    value: usize,
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Returns the value the entry is for.
+    #[must_use]
+    pub fn get(&self) -> usize {
+        self.value
+    }
+
+    /// Inserts the value into the set.
+    ///
+    /// The value was inserted when the entry was made,
+    /// so this only keeps it there.
+    pub fn insert(self) {
+        core::mem::forget(self);
+    }
+}
+
+impl<'a> Drop for VacantEntry<'a> {
+    fn drop(&mut self) {
+        let removed = self.tree.remove(self.value);
+        debug_assert!(removed, "a vacant entry's value was inserted");
+    }
+}
+
+#[cfg(test)]
+mod entry_tests {
+    use super::{Entry, EntrySet, SizedVEBTree, VEBTree};
+
+    /// Counts the calls an entry makes into the set.
+    #[derive(Debug, Default)]
+    struct Counting {
+        tree: SizedVEBTree<8>,
+        calls: usize,
+    }
+
+    impl EntrySet for Counting {
+        fn insert(&mut self, x: usize) -> bool {
+            self.calls += 1;
+            self.tree.insert(x)
+        }
+
+        fn remove(&mut self, x: usize) -> bool {
+            self.calls += 1;
+            self.tree.remove(x)
+        }
+    }
+
+    #[test]
+    fn vacant_insert_is_one_traversal() {
+        let mut set = Counting::default();
+        match Entry::new(&mut set, 5) {
+            Entry::Vacant(entry) => entry.insert(),
+            Entry::Occupied(_) => unreachable!(),
+        }
+        assert_eq!(set.calls, 1);
+        assert!(set.tree.contains(5));
+
+        Entry::new(&mut set, 7).or_insert();
+        assert_eq!(set.calls, 2);
+        assert!(set.tree.contains(7));
+    }
+
+    #[test]
+    fn occupied_is_one_traversal() {
+        let mut set = Counting::default();
+        set.tree.insert(5);
+        Entry::new(&mut set, 5).or_insert();
+        assert_eq!(set.calls, 1);
+        assert_eq!(set.tree.len(), 1);
+
+        match Entry::new(&mut set, 5) {
+            Entry::Occupied(entry) => assert_eq!(entry.remove(), 5),
+            Entry::Vacant(_) => unreachable!(),
+        }
+        assert_eq!(set.calls, 3);
+        assert!(set.tree.is_empty());
+    }
+
+    #[test]
+    fn dropped_vacant_entry_leaves_set_unchanged() {
+        let mut set = Counting::default();
+        assert!(!Entry::new(&mut set, 5).is_occupied());
+        assert_eq!(set.calls, 2);
+        assert!(set.tree.is_empty());
+    }
 }
@@ -0,0 +1,202 @@
+use crate::{new_boxed, sizes::GetVEBTreeSize, InnerVEBTree, SizedVEBTree, VEBTree};
+use alloc::boxed::Box;
+
+/// An ordered map with keys smaller than `1 << BITS`.
+///
+/// The keys are stored in a `SizedVEBTree<BITS>`,
+/// so successor and predecessor queries
+/// take O(log log U) time like in the set.
+/// The values are stored in a heap allocated array
+/// with one slot for every possible key,
+/// and the tree is heap allocated too,
+/// so big maps don't overflow the stack.
+///
+/// ```
+/// let mut map = flat_veb::VEBMap::<16, &str>::new();
+/// map.insert(7, "seven");
+/// map.insert(300, "three hundred");
+///
+/// assert_eq!(map.get(7), Some(&"seven"));
+/// assert_eq!(map.next_key(8), Some(300));
+/// assert_eq!(map.remove(7), Some("seven"));
+/// assert_eq!(map.prev_key(299), None);
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct VEBMap<const BITS: usize, V>
+where
+    (): GetVEBTreeSize<BITS>,
+{
+    keys: Box<SizedVEBTree<BITS>>,
+    values: Box<[Option<V>]>,
+}
+
+impl<const BITS: usize, V: Clone> Clone for VEBMap<BITS, V>
+where
+    (): GetVEBTreeSize<BITS>,
+{
+    /// Clones the map without putting
+    /// the tree of keys on the stack.
+    fn clone(&self) -> Self {
+        let mut keys = new_boxed::<SizedVEBTree<BITS>>();
+        keys.clone_from(&self.keys);
+        Self {
+            keys,
+            values: self.values.clone(),
+        }
+    }
+}
+
+impl<const BITS: usize, V> Default for VEBMap<BITS, V>
+where
+    (): GetVEBTreeSize<BITS>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BITS: usize, V> VEBMap<BITS, V>
+where
+    (): GetVEBTreeSize<BITS>,
+{
+    /// The map can hold keys in [0, CAPACITY).
+    pub const CAPACITY: usize = SizedVEBTree::<BITS>::CAPACITY;
+
+    /// Makes an empty map.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            keys: new_boxed(),
+            values: core::iter::repeat_with(|| None)
+                .take(Self::CAPACITY)
+                .collect(),
+        }
+    }
+
+    /// Returns the set of keys in the map.
+    #[must_use]
+    pub fn keys(&self) -> &SizedVEBTree<BITS> {
+        &self.keys
+    }
+
+    /// Returns the number of keys in the map.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns true if the map has no keys.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Removes every key and value from the map.
+    pub fn clear(&mut self) {
+        for key in self.keys.drain() {
+            self.values[key] = None;
+        }
+    }
+
+    /// Returns true if the map has a value for key.
+    #[must_use]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Returns a reference to the value for key, if any.
+    #[must_use]
+    pub fn get(&self, key: usize) -> Option<&V> {
+        self.values.get(key)?.as_ref()
+    }
+
+    /// Returns a mutable reference to the value for key, if any.
+    #[must_use]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut V> {
+        self.values.get_mut(key)?.as_mut()
+    }
+
+    /// Sets the value for key,
+    /// returning the old value if there was one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if key is not less than `CAPACITY`.
+    pub fn insert(&mut self, key: usize, value: V) -> Option<V> {
+        let old = self.values[key].replace(value);
+        if old.is_none() {
+            self.keys.insert(key);
+        }
+        old
+    }
+
+    /// Removes key from the map, returning its value if there was one.
+    pub fn remove(&mut self, key: usize) -> Option<V> {
+        let old = self.values.get_mut(key)?.take();
+        if old.is_some() {
+            self.keys.remove(key);
+        }
+        old
+    }
+
+    /// Returns the smallest key in the map
+    /// that is greater or equal to key, if any.
+    #[must_use]
+    pub fn next_key(&self, key: usize) -> Option<usize> {
+        self.keys.next(key)
+    }
+
+    /// Returns the biggest key in the map
+    /// that is less or equal to key, if any.
+    #[must_use]
+    pub fn prev_key(&self, key: usize) -> Option<usize> {
+        self.keys.prev(key)
+    }
+
+    /// Returns the smallest key in the map
+    /// that is greater or equal to key,
+    /// together with its value, if any.
+    #[must_use]
+    pub fn next_key_value(&self, key: usize) -> Option<(usize, &V)> {
+        let key = self.next_key(key)?;
+        Some((key, self.value_of(key)))
+    }
+
+    /// Returns the biggest key in the map
+    /// that is less or equal to key,
+    /// together with its value, if any.
+    #[must_use]
+    pub fn prev_key_value(&self, key: usize) -> Option<(usize, &V)> {
+        let key = self.prev_key(key)?;
+        Some((key, self.value_of(key)))
+    }
+
+    /// Returns an iterator over the keys in the range [lo, hi)
+    /// in ascending order, together with their values.
+    ///
+    /// hi is clamped to the capacity,
+    /// and the range is empty if lo >= hi.
+    #[must_use]
+    pub fn range(&self, lo: usize, hi: usize) -> impl DoubleEndedIterator<Item = (usize, &V)> {
+        self.keys.range(lo, hi).map(|key| (key, self.value_of(key)))
+    }
+
+    /// Returns the value of a key known to be in the map.
+    fn value_of(&self, key: usize) -> &V {
+        self.values[key]
+            .as_ref()
+            .expect("every key in the tree has a value")
+    }
+
+    /// Returns the smallest key in the map, if any.
+    #[must_use]
+    pub fn first_key(&self) -> Option<usize> {
+        self.keys.first()
+    }
+
+    /// Returns the biggest key in the map, if any.
+    #[must_use]
+    pub fn last_key(&self) -> Option<usize> {
+        self.keys.last()
+    }
+}
@@ -0,0 +1,166 @@
+use crate::InnerVEBTree;
+
+/// A set of signed integers in a range [lo, hi),
+/// stored as their offsets from lo in a `VEBTree`.
+///
+/// ```
+/// use flat_veb::{OffsetVEBTree, SizedVEBTree};
+///
+/// let mut tree = OffsetVEBTree::<SizedVEBTree<11>>::new_with_range(-1000, 1000);
+/// tree.insert(-5);
+/// tree.insert(7);
+///
+/// assert_eq!(tree.next(-4), Some(7));
+/// assert_eq!(tree.prev(6), Some(-5));
+/// assert!(!tree.contains(5000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffsetVEBTree<T: InnerVEBTree> {
+    tree: T,
+    lo: i64,
+    hi: i64,
+}
+
+impl<T: InnerVEBTree> OffsetVEBTree<T> {
+    /// Makes an empty set which can hold integers in [lo, hi).
+    ///
+    /// # Panics
+    ///
+    /// Panics if hi < lo, or if there are
+    /// more than `T::CAPACITY` integers in the range.
+    #[must_use]
+    pub fn new_with_range(lo: i64, hi: i64) -> Self {
+        assert!(lo <= hi, "Empty range: [{lo}, {hi}).");
+        assert!(
+            hi.abs_diff(lo) <= T::CAPACITY as u64,
+            "Too big range: [{lo}, {hi}). The capacity is {}.",
+            T::CAPACITY
+        );
+        Self {
+            tree: T::default(),
+            lo,
+            hi,
+        }
+    }
+
+    /// The smallest integer the set can hold.
+    #[must_use]
+    pub fn lo(&self) -> i64 {
+        self.lo
+    }
+
+    /// One more than the biggest integer the set can hold.
+    #[must_use]
+    pub fn hi(&self) -> i64 {
+        self.hi
+    }
+
+    /// Returns the set of offsets from lo.
+    #[must_use]
+    pub fn inner(&self) -> &T {
+        &self.tree
+    }
+
+    /// Returns the offset of x from lo,
+    /// if x is in the range of the set.
+    fn offset_of(&self, x: i64) -> Option<usize> {
+        if (self.lo..self.hi).contains(&x) {
+            usize::try_from(x.abs_diff(self.lo)).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value with the given offset from lo.
+    fn value_at(&self, offset: usize) -> i64 {
+        self.lo
+            .checked_add_unsigned(offset as u64)
+            .expect("offsets are less than hi - lo")
+    }
+
+    /// Returns the number of elements in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Clears the set, removing all elements.
+    pub fn clear(&mut self) {
+        self.tree.clear();
+    }
+
+    /// Returns true if the set contains x.
+    /// Integers outside of the range are never contained.
+    #[must_use]
+    pub fn contains(&self, x: i64) -> bool {
+        self.offset_of(x)
+            .is_some_and(|offset| self.tree.contains(offset))
+    }
+
+    /// Adds x to the set.
+    ///
+    /// If the set did not have x present, true is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if x is not in [lo, hi).
+    pub fn insert(&mut self, x: i64) -> bool {
+        let offset = self.offset_of(x).unwrap_or_else(|| {
+            panic!(
+                "Value out of range: {x}. The range is [{}, {}).",
+                self.lo, self.hi
+            )
+        });
+        self.tree.insert(offset)
+    }
+
+    /// Removes x from the set.
+    ///
+    /// If the set did have x present, true is returned.
+    /// Integers outside of the range are never present.
+    pub fn remove(&mut self, x: i64) -> bool {
+        match self.offset_of(x) {
+            Some(offset) => self.tree.remove(offset),
+            None => false,
+        }
+    }
+
+    /// Returns the first element in the set, if any.
+    #[must_use]
+    pub fn first(&self) -> Option<i64> {
+        Some(self.value_at(self.tree.first()?))
+    }
+
+    /// Returns the last element in the set, if any.
+    #[must_use]
+    pub fn last(&self) -> Option<i64> {
+        Some(self.value_at(self.tree.last()?))
+    }
+
+    /// Returns the smallest integer in the
+    /// set that is greater or equal to x, if any.
+    #[must_use]
+    pub fn next(&self, x: i64) -> Option<i64> {
+        let offset = self.offset_of(x.max(self.lo))?;
+        Some(self.value_at(self.tree.next(offset)?))
+    }
+
+    /// Returns the biggest integer in the
+    /// set that is less or equal to x, if any.
+    #[must_use]
+    pub fn prev(&self, x: i64) -> Option<i64> {
+        let offset = self.offset_of(x.min(self.hi.saturating_sub(1)))?;
+        Some(self.value_at(self.tree.prev(offset)?))
+    }
+
+    /// Returns an iterator over the values in the set.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = i64> + ExactSizeIterator + '_ {
+        self.tree.iter().map(|offset| self.value_at(offset))
+    }
+}
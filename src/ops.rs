@@ -0,0 +1,67 @@
+use crate::{
+    bit_array::BitArraySet,
+    outer,
+    private::ConditionalHasDeepMaybeUninit,
+    small_set::{Bits, SmallSet},
+    InnerVEBTree,
+};
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+
+/// Implements an operator and its assigning variant
+/// for all the set types, using the in-place set operation.
+macro_rules! impl_set_op {
+    ($op:ident::$op_fn:ident, $assign:ident::$assign_fn:ident, $with:ident) => {
+        impl_set_op! {
+            @for [const BITS: usize, T: Bits] SmallSet<BITS, T>,
+            [],
+            $op::$op_fn, $assign::$assign_fn, $with
+        }
+        impl_set_op! {
+            @for [const BITS: usize, const WORDS: usize] BitArraySet<BITS, WORDS>,
+            [[(); WORDS]: ConditionalHasDeepMaybeUninit],
+            $op::$op_fn, $assign::$assign_fn, $with
+        }
+        impl_set_op! {
+            @for [const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree]
+            outer::VEBTree<UPPER_CAPACITY, Upper, Lower>,
+            [[(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit],
+            $op::$op_fn, $assign::$assign_fn, $with
+        }
+    };
+    (
+        @for [$($generics:tt)*] $type:ty,
+        [$($bounds:tt)*],
+        $op:ident::$op_fn:ident, $assign:ident::$assign_fn:ident, $with:ident
+    ) => {
+        impl<$($generics)*> $op<&$type> for &$type
+        where
+            $($bounds)*
+        {
+            type Output = $type;
+
+            fn $op_fn(self, rhs: &$type) -> $type {
+                let mut result = *self;
+                result.$with(rhs);
+                result
+            }
+        }
+
+        impl<$($generics)*> $assign<&$type> for $type
+        where
+            $($bounds)*
+        {
+            fn $assign_fn(&mut self, rhs: &$type) {
+                self.$with(rhs);
+            }
+        }
+    };
+}
+
+impl_set_op!(BitOr::bitor, BitOrAssign::bitor_assign, union_with);
+impl_set_op!(BitAnd::bitand, BitAndAssign::bitand_assign, intersect_with);
+impl_set_op!(Sub::sub, SubAssign::sub_assign, difference_with);
+impl_set_op!(
+    BitXor::bitxor,
+    BitXorAssign::bitxor_assign,
+    symmetric_difference_with
+);
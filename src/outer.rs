@@ -1,13 +1,16 @@
 use crate::{
     private::{ConditionalHasDeepMaybeUninit, Sealed},
-    InnerVEBTree,
+    InnerVEBTree, IntoIter, VEBIterator,
 };
+use core::any::Any;
 #[cfg(feature = "dyn_capacity")]
 use deep_maybe_uninit::{DeepMaybeUninit, HasDeepMaybeUninit};
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 /// Recursive implementation of a van Emde Boas Tree.
 #[cfg_attr(feature = "dyn_capacity", derive(DeepMaybeUninit))]
-#[derive(Clone, Copy)]
+#[derive(Copy)]
 #[repr(C)]
 pub struct VEBTree<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree>
 where
@@ -17,6 +20,7 @@ where
     lower: [Lower; UPPER_CAPACITY],
     min: usize,
     max: usize,
+    len: usize,
 }
 
 impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Sealed
@@ -26,6 +30,25 @@ where
 {
 }
 
+// clone_from is written by hand on purpose, see its doc comment,
+// and benches/clone_from.rs for the comparison with a plain copy.
+#[allow(clippy::expl_impl_clone_on_copy, clippy::non_canonical_clone_impl)]
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Clone
+    for VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+
+    /// Only copies the `lower` blocks that are non-empty
+    /// in either tree, since the rest are empty in both.
+    fn clone_from(&mut self, source: &Self) {
+        self.reuse_as(source);
+    }
+}
+
 impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Default
     for VEBTree<UPPER_CAPACITY, Upper, Lower>
 where
@@ -36,6 +59,54 @@ where
     }
 }
 
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> PartialEq
+    for VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn eq(&self, other: &Self) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return self.is_empty() && other.is_empty();
+        }
+        if self.len != other.len
+            || self.min != other.min
+            || self.max != other.max
+            || self.upper != other.upper
+        {
+            return false;
+        }
+
+        let mut block = self.upper.first();
+        while let Some(ux) = block {
+            if self.lower[ux] != other.lower[ux] {
+                return false;
+            }
+            block = self.following_block(ux);
+        }
+        true
+    }
+}
+
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Eq
+    for VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+}
+
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> core::hash::Hash
+    for VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for x in crate::VEBTree::iter(self) {
+            x.hash(state);
+        }
+    }
+}
+
 impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> core::fmt::Debug
     for VEBTree<UPPER_CAPACITY, Upper, Lower>
 where
@@ -46,12 +117,168 @@ where
     }
 }
 
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> core::fmt::Display
+    for VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        crate::fmt_runs(self, f)
+    }
+}
+
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> FromIterator<usize>
+    for VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Extend<usize>
+    for VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
+
+impl<'a, const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Extend<&'a usize>
+    for VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn extend<I: IntoIterator<Item = &'a usize>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> IntoIterator
+    for &'a VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    type Item = usize;
+    type IntoIter = VEBIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        crate::VEBTree::iter(self)
+    }
+}
+
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> IntoIterator
+    for VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    type Item = usize;
+    type IntoIter = IntoIter<Self>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
 impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> InnerVEBTree
     for VEBTree<UPPER_CAPACITY, Upper, Lower>
 where
     [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
 {
     const BITS: usize = Upper::BITS + Lower::BITS;
+
+    // Lower has at least as many bits as Upper,
+    // so it is never the shallower of the two.
+    const HEIGHT: usize = 1 + Lower::HEIGHT;
+
+    #[cfg(feature = "dyn_capacity")]
+    fn init(value: &mut <Self as HasDeepMaybeUninit>::AsDeepMaybeUninit) {
+        Self::init(value);
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        self.union_with(other);
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        self.intersect_with(other);
+    }
+
+    fn difference_with(&mut self, other: &Self) {
+        self.difference_with(other);
+    }
+
+    fn symmetric_difference_with(&mut self, other: &Self) {
+        self.symmetric_difference_with(other);
+    }
+
+    fn complement(&self) -> Self {
+        self.complement()
+    }
+
+    fn diff(&self, other: &Self) -> (Self, Self) {
+        self.diff(other)
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.is_subset(other)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.is_disjoint(other)
+    }
+
+    fn intersection_len(&self, other: &Self) -> usize {
+        self.intersection_len(other)
+    }
+
+    fn split_off(&mut self, x: usize) -> Self {
+        self.split_off(x)
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.append(other);
+    }
+
+    fn reuse_as(&mut self, other: &Self) {
+        self.reuse_as(other);
+    }
+
+    fn put_block<B: InnerVEBTree>(&mut self, offset: usize, block: &B) -> bool {
+        self.put_block(offset, block)
+    }
+
+    fn put_into<T: InnerVEBTree>(&self, target: &mut T, offset: usize) {
+        self.put_into(target, offset);
+    }
+
+    fn shift_by(&self, offset: isize) -> Option<Self> {
+        self.shift_by(offset)
+    }
+
+    fn from_sorted_shifted(xs: &[usize], offset: usize) -> Self {
+        Self::from_sorted_shifted(xs, offset)
+    }
+
+    fn from_bitmask(words: &[u64]) -> Self {
+        Self::from_bitmask(words)
+    }
+
+    fn write_bitmask(&self, words: &mut [u64]) {
+        self.write_bitmask(words);
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_iter(&self) -> impl ParallelIterator<Item = usize> + '_ {
+        self.par_iter()
+    }
 }
 
 impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree>
@@ -65,6 +292,7 @@ where
             lower: [Default::default(); UPPER_CAPACITY],
             min: usize::MAX,
             max: usize::MAX,
+            len: 0,
         }
     }
 
@@ -76,6 +304,7 @@ where
         }
         value.min = usize::MAX.forget_init();
         value.max = usize::MAX.forget_init();
+        value.len = 0usize.forget_init();
     }
 
     fn ul(x: usize) -> (usize, usize) {
@@ -84,6 +313,149 @@ where
         (ux, lx)
     }
 
+    /// Returns the part of the range [lo, hi) inside block ux,
+    /// as a range of positions in that block.
+    fn block_part(lo: usize, hi: usize, ux: usize) -> (usize, usize) {
+        let start = ux << Lower::BITS;
+        let block_lo = lo.max(start) - start;
+        let block_hi = hi.min(start + Lower::CAPACITY) - start;
+        (block_lo, block_hi)
+    }
+
+    /// Splits the non-empty range [lo, hi) into the parts
+    /// inside each block, as `(ux, block_lo, block_hi)`.
+    fn split_range(lo: usize, hi: usize) -> impl Iterator<Item = (usize, usize, usize)> {
+        debug_assert!(lo < hi && hi <= Self::CAPACITY);
+        (lo >> Lower::BITS..=(hi - 1) >> Lower::BITS).map(move |ux| {
+            let (block_lo, block_hi) = Self::block_part(lo, hi, ux);
+            (ux, block_lo, block_hi)
+        })
+    }
+
+    /// Returns the first non-empty block after ux, if any.
+    fn following_block(&self, ux: usize) -> Option<usize> {
+        if ux + 1 == UPPER_CAPACITY {
+            return None;
+        }
+        self.upper.next(ux + 1)
+    }
+
+    /// Returns the first block which is
+    /// non-empty in either self or other, if any.
+    fn first_block(&self, other: &Self) -> Option<usize> {
+        match (self.upper.first(), other.upper.first()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Returns the first block after ux which is
+    /// non-empty in either self or other, if any.
+    fn next_block(&self, other: &Self, ux: usize) -> Option<usize> {
+        match (self.following_block(ux), other.following_block(ux)) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Applies a set operation to self and other,
+    /// storing the result in self.
+    ///
+    /// `block_op` is applied to every pair of `lower` blocks
+    /// where at least one of them is non-empty,
+    /// and has to leave a block empty if both were empty.
+    /// `keep` tells whether an element should be in the result,
+    /// given whether it is in self and in other, and is used
+    /// for the `min`s which are not stored in the `lower` blocks.
+    fn combine(
+        &mut self,
+        other: &Self,
+        keep: impl Fn(bool, bool) -> bool,
+        block_op: impl Fn(&mut Lower, &Lower),
+    ) {
+        let mut fixes = [None, None];
+        if !self.is_empty() {
+            fixes[0] = Some((self.min, keep(true, other.contains(self.min))));
+        }
+        if !other.is_empty() {
+            fixes[1] = Some((other.min, keep(self.contains(other.min), true)));
+        }
+
+        let mut lower_len = 0;
+        let mut block = self.first_block(other);
+        while let Some(ux) = block {
+            block_op(&mut self.lower[ux], &other.lower[ux]);
+            if self.lower[ux].is_empty() {
+                self.upper.remove(ux);
+            } else {
+                self.upper.insert(ux);
+                lower_len += self.lower[ux].len();
+            }
+            block = self.next_block(other, ux);
+        }
+
+        self.rebuild(lower_len);
+
+        for (x, keep) in fixes.into_iter().flatten() {
+            if keep {
+                self.insert(x);
+            } else {
+                self.remove(x);
+            }
+        }
+    }
+
+    /// Restores `min`, `max` and `len` after
+    /// the `lower` blocks were changed directly,
+    /// moving the smallest element out of its block.
+    /// Expects `upper` to be up to date, and
+    /// `lower_len` to be the sum of the block lengths.
+    fn rebuild(&mut self, lower_len: usize) {
+        let Some(min) = self.pop_block_min() else {
+            self.set_empty();
+            return;
+        };
+
+        self.min = min;
+        self.max = self.block_max_or_min();
+        self.len = lower_len;
+    }
+
+    /// Marks the set as empty,
+    /// expecting the `lower` blocks to already be empty.
+    fn set_empty(&mut self) {
+        self.min = usize::MAX;
+        self.max = usize::MAX;
+        self.len = 0;
+    }
+
+    /// Removes the smallest element stored in
+    /// the `lower` blocks and returns it, if any.
+    fn pop_block_min(&mut self) -> Option<usize> {
+        let ux = self.upper.first()?;
+        let lx = self.lower[ux]
+            .pop_first()
+            .expect("upper only marks non-empty blocks");
+        if self.lower[ux].is_empty() {
+            self.upper.pop_first();
+        }
+        Some((ux << Lower::BITS) + lx)
+    }
+
+    /// Returns the largest element stored in the `lower` blocks,
+    /// or `min` if they are empty.
+    fn block_max_or_min(&self) -> usize {
+        match self.upper.last() {
+            Some(ux) => {
+                (ux << Lower::BITS)
+                    + self.lower[ux]
+                        .last()
+                        .expect("upper only marks non-empty blocks")
+            }
+            None => self.min,
+        }
+    }
+
     pub fn capacity() -> usize {
         Self::CAPACITY
     }
@@ -95,16 +467,89 @@ where
         }
         self.min = usize::MAX;
         self.max = usize::MAX;
+        self.len = 0;
+    }
+
+    pub fn fill(&mut self) {
+        self.upper.fill();
+        for low in &mut self.lower {
+            low.fill();
+        }
+        self.rebuild(Self::CAPACITY);
     }
 
     pub fn is_empty(&self) -> bool {
+        debug_assert_eq!(self.min == usize::MAX, self.len == 0);
         self.min == usize::MAX
     }
 
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn count(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let mut count = 1;
+        let mut block = self.upper.first();
+        while let Some(ux) = block {
+            count += self.lower[ux].count();
+            block = self.following_block(ux);
+        }
+        count
+    }
+
+    pub fn rank(&self, x: usize) -> usize {
+        if self.is_empty() || x <= self.min {
+            return 0;
+        }
+        if x > self.max {
+            return self.len;
+        }
+
+        let (ux, lx) = Self::ul(x);
+        let mut count = 1 + self.lower[ux].rank(lx);
+
+        let mut uy = 0;
+        while let Some(block) = self.upper.next(uy) {
+            if block >= ux {
+                break;
+            }
+            count += self.lower[block].len();
+            uy = block + 1;
+        }
+
+        count
+    }
+
+    pub fn select(&self, k: usize) -> Option<usize> {
+        if k >= self.len {
+            return None;
+        }
+        if k == 0 {
+            return Some(self.min);
+        }
+
+        // min is not in the blocks, so it is skipped first.
+        let mut k = k - 1;
+        let mut ux = self.upper.first()?;
+        loop {
+            let len = self.lower[ux].len();
+            if k < len {
+                return Some((ux << Lower::BITS) + self.lower[ux].select(k)?);
+            }
+            k -= len;
+            ux = self.following_block(ux)?;
+        }
+    }
+
     pub fn contains(&self, x: usize) -> bool {
         debug_assert!(x < Self::CAPACITY);
 
-        if x < self.min {
+        // Out of range x are never in the set,
+        // even if x is the sentinel min of an empty set.
+        if x < self.min || x >= Self::CAPACITY {
             return false;
         }
 
@@ -130,6 +575,7 @@ where
         if self.is_empty() {
             self.min = x;
             self.max = x;
+            self.len = 1;
             return true;
         }
 
@@ -149,7 +595,89 @@ where
         if self.lower[ux].is_empty() {
             self.upper.insert(ux);
         }
-        self.lower[ux].insert(lx)
+        let inserted = self.lower[ux].insert(lx);
+        if inserted {
+            self.len += 1;
+        }
+        inserted
+    }
+
+    pub fn insert_and_return_neighbor(&mut self, x: usize) -> (bool, Option<usize>) {
+        debug_assert!(x < Self::CAPACITY);
+
+        if self.is_empty() || x < self.min {
+            let successor = (!self.is_empty()).then_some(self.min);
+            self.insert(x);
+            return (true, successor);
+        }
+
+        if x == self.min {
+            let successor = self.upper.first().map(|ux| {
+                (ux << Lower::BITS)
+                    + self.lower[ux]
+                        .first()
+                        .expect("upper only marks non-empty blocks")
+            });
+            return (false, successor);
+        }
+
+        // Only one of the block and upper needs a recursive call:
+        // the block if the successor is inside it,
+        // and otherwise upper, to find the following block.
+        let (ux, lx) = Self::ul(x);
+        let successor_block = match self.lower[ux].last() {
+            Some(last) if lx < last => {
+                let (inserted, successor) = self.lower[ux].insert_and_return_neighbor(lx);
+                if inserted {
+                    self.len += 1;
+                }
+                let successor = successor.expect("lx < last");
+                return (inserted, Some((ux << Lower::BITS) + successor));
+            }
+            Some(_) => self.following_block(ux),
+            None => self.upper.insert_and_return_neighbor(ux).1,
+        };
+
+        if x > self.max {
+            self.max = x;
+        }
+        let inserted = self.lower[ux].insert(lx);
+        if inserted {
+            self.len += 1;
+        }
+
+        let successor = successor_block.map(|ux| {
+            (ux << Lower::BITS)
+                + self.lower[ux]
+                    .first()
+                    .expect("upper only marks non-empty blocks")
+        });
+        (inserted, successor)
+    }
+
+    pub fn insert_range(&mut self, lo: usize, hi: usize) {
+        let hi = hi.min(Self::CAPACITY);
+        if lo >= hi {
+            return;
+        }
+
+        // Afterwards self.min <= lo, so the rest
+        // of the range can go directly into the blocks.
+        self.insert(lo);
+        let lo = lo + 1;
+        if lo == hi {
+            return;
+        }
+
+        self.upper
+            .insert_range(lo >> Lower::BITS, ((hi - 1) >> Lower::BITS) + 1);
+        for (ux, block_lo, block_hi) in Self::split_range(lo, hi) {
+            let before = self.lower[ux].len();
+            self.lower[ux].insert_range(block_lo, block_hi);
+            self.len += self.lower[ux].len() - before;
+        }
+
+        self.max = self.max.max(hi - 1);
     }
 
     pub fn remove(&mut self, mut x: usize) -> bool {
@@ -157,8 +685,7 @@ where
 
         if self.min == self.max {
             return if x == self.min {
-                self.min = usize::MAX;
-                self.max = 0;
+                self.set_empty();
                 true
             } else {
                 false
@@ -180,6 +707,7 @@ where
                 self.max = self.prev(x - 1).expect("self.min != self.max");
             }
 
+            self.len -= 1;
             true
         } else {
             debug_assert!(x != self.max);
@@ -187,9 +715,157 @@ where
         }
     }
 
+    pub fn toggle(&mut self, x: usize) -> bool {
+        debug_assert!(x < Self::CAPACITY);
+
+        if x == self.min {
+            self.remove(x);
+            return false;
+        }
+        let (ux, lx) = Self::ul(x);
+        if self.is_empty() || x < self.min || x > self.max || self.lower[ux].is_empty() {
+            self.insert(x);
+            return true;
+        }
+
+        // x is in a non-empty block, so only that block
+        // is descended into, and upper only changes
+        // if the block becomes empty.
+        let present = self.lower[ux].toggle(lx);
+        if present {
+            self.len += 1;
+        } else {
+            self.len -= 1;
+            if self.lower[ux].is_empty() {
+                self.upper.remove(ux);
+            }
+            if x == self.max {
+                self.max = self.block_max_or_min();
+            }
+        }
+        present
+    }
+
+    pub fn toggle_range(&mut self, lo: usize, hi: usize) {
+        let hi = hi.min(Self::CAPACITY);
+        if lo >= hi {
+            return;
+        }
+
+        // min is put back into its block while toggling,
+        // so every element is in the blocks,
+        // and the smallest one is moved out again after.
+        if let Some(min) = self.first() {
+            let (ux, lx) = Self::ul(min);
+            self.lower[ux].insert(lx);
+            self.upper.insert(ux);
+        }
+
+        let mut lower_len = self.len;
+        for (ux, block_lo, block_hi) in Self::split_range(lo, hi) {
+            let block = &mut self.lower[ux];
+            let before = block.len();
+            if block_lo == 0 && block_hi == Lower::CAPACITY {
+                *block = block.complement();
+            } else {
+                block.toggle_range(block_lo, block_hi);
+            }
+            lower_len = lower_len - before + block.len();
+
+            if block.is_empty() {
+                self.upper.remove(ux);
+            } else {
+                self.upper.insert(ux);
+            }
+        }
+
+        self.rebuild(lower_len);
+    }
+
+    pub fn pop_first(&mut self) -> Option<usize> {
+        let min = self.first()?;
+        if min == self.max {
+            self.remove(min);
+            return Some(min);
+        }
+
+        self.min = self.pop_block_min().expect("self.min != self.max");
+        self.len -= 1;
+        Some(min)
+    }
+
+    pub fn pop_last(&mut self) -> Option<usize> {
+        let max = self.last()?;
+        if max == self.min {
+            self.remove(max);
+            return Some(max);
+        }
+
+        let ux = self.upper.last().expect("self.min != self.max");
+        self.lower[ux]
+            .pop_last()
+            .expect("upper only marks non-empty blocks");
+        if self.lower[ux].is_empty() {
+            self.upper.pop_last();
+        }
+
+        self.max = self.block_max_or_min();
+        self.len -= 1;
+        Some(max)
+    }
+
+    pub fn remove_range(&mut self, lo: usize, hi: usize) {
+        if self.is_empty() {
+            return;
+        }
+        let lo = lo.max(self.min);
+        let hi = hi.min(self.max + 1);
+        if lo >= hi {
+            return;
+        }
+
+        let removes_min = lo == self.min;
+        let blocks_lo = if removes_min { lo + 1 } else { lo };
+        if blocks_lo < hi {
+            let first = blocks_lo >> Lower::BITS;
+            let last = (hi - 1) >> Lower::BITS;
+
+            let mut block = self.upper.next(first);
+            while let Some(ux) = block.filter(|&ux| ux <= last) {
+                let before = self.lower[ux].len();
+                let (part_lo, part_hi) = Self::block_part(blocks_lo, hi, ux);
+                self.lower[ux].remove_range(part_lo, part_hi);
+                self.len -= before - self.lower[ux].len();
+                block = self.following_block(ux);
+            }
+
+            // Blocks strictly inside the range are now empty,
+            // and are dropped from upper together.
+            self.upper.remove_range(first + 1, last);
+            for ux in [first, last] {
+                if self.lower[ux].is_empty() {
+                    self.upper.remove(ux);
+                }
+            }
+        }
+
+        if removes_min {
+            self.len -= 1;
+            let Some(min) = self.pop_block_min() else {
+                self.set_empty();
+                return;
+            };
+            self.min = min;
+        }
+        if hi > self.max {
+            self.max = self.block_max_or_min();
+        }
+    }
+
     pub fn next(&self, x: usize) -> Option<usize> {
         debug_assert!(x < Self::CAPACITY);
 
+        // Also covers x >= CAPACITY, since max < CAPACITY.
         if self.is_empty() || x > self.max {
             return None;
         }
@@ -213,7 +889,7 @@ where
     pub fn prev(&self, x: usize) -> Option<usize> {
         debug_assert!(x < Self::CAPACITY);
 
-        if self.is_empty() || x < self.min {
+        if self.is_empty() || x < self.min || x >= Self::CAPACITY {
             return None;
         }
         let (ux, lx) = Self::ul(x);
@@ -233,6 +909,408 @@ where
         Some(self.min)
     }
 
+    pub fn next_gap(&self, mut x: usize) -> Option<usize> {
+        debug_assert!(x < Self::CAPACITY);
+
+        if self.is_empty() || x < self.min {
+            return Some(x);
+        }
+        if x == self.min {
+            if x + 1 == Self::CAPACITY {
+                return None;
+            }
+            x += 1;
+        }
+        if x > self.max {
+            return Some(x);
+        }
+
+        let (mut ux, lx) = Self::ul(x);
+        if let Some(lx) = self.lower[ux].next_gap(lx) {
+            return Some((ux << Lower::BITS) + lx);
+        }
+
+        loop {
+            ux += 1;
+            if ux == UPPER_CAPACITY {
+                return None;
+            }
+            if let Some(lx) = self.lower[ux].next_gap(0) {
+                return Some((ux << Lower::BITS) + lx);
+            }
+        }
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        self.combine(other, |a, b| a || b, Lower::union_with);
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.combine(other, |a, b| a && b, Lower::intersect_with);
+    }
+
+    pub fn difference_with(&mut self, other: &Self) {
+        self.combine(other, |a, b| a && !b, Lower::difference_with);
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &Self) {
+        self.combine(other, |a, b| a != b, Lower::symmetric_difference_with);
+    }
+
+    pub fn diff(&self, other: &Self) -> (Self, Self) {
+        let mut only_self = Self::new();
+        let mut only_other = Self::new();
+        let mut self_len = 0;
+        let mut other_len = 0;
+
+        let mut block = self.first_block(other);
+        while let Some(ux) = block {
+            let (a, b) = self.lower[ux].diff(&other.lower[ux]);
+            if !a.is_empty() {
+                only_self.upper.insert(ux);
+                self_len += a.len();
+            }
+            if !b.is_empty() {
+                only_other.upper.insert(ux);
+                other_len += b.len();
+            }
+            only_self.lower[ux] = a;
+            only_other.lower[ux] = b;
+            block = self.next_block(other, ux);
+        }
+        only_self.rebuild(self_len);
+        only_other.rebuild(other_len);
+
+        // The mins are not in the blocks, so each min
+        // has to be added to its own side if the other set lacks it,
+        // and removed from the other side, where a block can have it.
+        if let Some(min) = self.first() {
+            if !other.contains(min) {
+                only_self.insert(min);
+            }
+            only_other.remove(min);
+        }
+        if let Some(min) = other.first() {
+            if !self.contains(min) {
+                only_other.insert(min);
+            }
+            only_self.remove(min);
+        }
+
+        (only_self, only_other)
+    }
+
+    pub fn complement(&self) -> Self {
+        // Empty blocks become full and the other way around,
+        // so every block is visited and the rest is rebuilt.
+        let min = self.first().map(Self::ul);
+        let mut result = Self::new();
+        let mut lower_len = 0;
+        for ux in 0..UPPER_CAPACITY {
+            let mut block = self.lower[ux].complement();
+            if let Some((_, min_lx)) = min.filter(|&(min_ux, _)| min_ux == ux) {
+                block.remove(min_lx);
+            }
+            if !block.is_empty() {
+                result.upper.insert(ux);
+                lower_len += block.len();
+            }
+            result.lower[ux] = block;
+        }
+        result.rebuild(lower_len);
+        result
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        if self.len > other.len || !other.contains(self.min) {
+            return false;
+        }
+
+        let other_min = Self::ul(other.min);
+        let mut block = self.upper.first();
+        while let Some(ux) = block {
+            let is_subset = if ux == other_min.0 && self.lower[ux].contains(other_min.1) {
+                let mut lower = self.lower[ux];
+                lower.remove(other_min.1);
+                lower.is_subset(&other.lower[ux])
+            } else {
+                self.lower[ux].is_subset(&other.lower[ux])
+            };
+            if !is_subset {
+                return false;
+            }
+            block = self.following_block(ux);
+        }
+
+        true
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return true;
+        }
+        if other.contains(self.min) || self.contains(other.min) {
+            return false;
+        }
+
+        let mut block = self.upper.first();
+        while let Some(ux) = block {
+            if !other.lower[ux].is_empty() && !self.lower[ux].is_disjoint(&other.lower[ux]) {
+                return false;
+            }
+            block = self.following_block(ux);
+        }
+
+        true
+    }
+
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        if self.is_empty() || other.is_empty() {
+            return 0;
+        }
+
+        // The mins are not in the blocks, so they are checked separately,
+        // and only the blocks non-empty in both sets are visited.
+        let mut count = usize::from(other.contains(self.min));
+        if other.min != self.min && self.contains(other.min) {
+            count += 1;
+        }
+
+        let mut block = self.upper.first();
+        while let Some(ux) = block {
+            if !other.lower[ux].is_empty() {
+                count += self.lower[ux].intersection_len(&other.lower[ux]);
+            }
+            block = self.following_block(ux);
+        }
+        count
+    }
+
+    pub fn split_off(&mut self, x: usize) -> Self {
+        let mut other = Self::new();
+        if self.is_empty() || x > self.max {
+            return other;
+        }
+        if x <= self.min {
+            core::mem::swap(self, &mut other);
+            return other;
+        }
+
+        // Now self keeps its min, and whole blocks
+        // after the one x is in can be moved to other.
+        let (ux, lx) = Self::ul(x);
+        let mut moved_len = 0;
+        if self.upper.contains(ux) {
+            other.lower[ux] = self.lower[ux].split_off(lx);
+            if !other.lower[ux].is_empty() {
+                other.upper.insert(ux);
+                moved_len += other.lower[ux].len();
+            }
+            if self.lower[ux].is_empty() {
+                self.upper.remove(ux);
+            }
+        }
+
+        let mut block = self.following_block(ux);
+        while let Some(vx) = block {
+            other.lower[vx] = core::mem::take(&mut self.lower[vx]);
+            other.upper.insert(vx);
+            moved_len += other.lower[vx].len();
+            block = self.following_block(vx);
+        }
+        self.upper.remove_range(ux + 1, UPPER_CAPACITY);
+
+        self.len -= moved_len;
+        self.max = self.block_max_or_min();
+        other.rebuild(moved_len);
+        other
+    }
+
+    pub fn put_block<B: InnerVEBTree>(&mut self, offset: usize, block: &B) -> bool {
+        if B::CAPACITY > Lower::CAPACITY {
+            return false;
+        }
+        let (Some(first), Some(last)) = (block.first(), block.last()) else {
+            return true;
+        };
+        debug_assert!(self.is_empty() || self.max < offset + first);
+
+        // min is not stored in the blocks,
+        // so it is taken out of the block first.
+        let mut rest = *block;
+        if self.is_empty() {
+            rest.remove(first);
+            self.min = offset + first;
+            self.max = offset + first;
+            self.len = 1;
+            if rest.is_empty() {
+                return true;
+            }
+        }
+
+        let (ux, lx) = Self::ul(offset);
+        match (&rest as &dyn Any).downcast_ref::<Lower>() {
+            Some(&lower) => self.lower[ux] = lower,
+            None => rest.put_into(&mut self.lower[ux], lx),
+        }
+        self.upper.insert(ux);
+        self.len += rest.len();
+        self.max = offset + last;
+        true
+    }
+
+    pub fn put_into<T: InnerVEBTree>(&self, target: &mut T, offset: usize) {
+        if self.is_empty() || target.put_block(offset, self) {
+            return;
+        }
+
+        target.insert(offset + self.min);
+        let mut block = self.upper.first();
+        while let Some(ux) = block {
+            self.lower[ux].put_into(target, offset + (ux << Lower::BITS));
+            block = self.following_block(ux);
+        }
+    }
+
+    pub fn reuse_as(&mut self, other: &Self) {
+        // The blocks are overwritten where they are,
+        // so no copy of the whole tree is made on the stack.
+        let mut block = self.first_block(other);
+        while let Some(ux) = block {
+            self.lower[ux].reuse_as(&other.lower[ux]);
+            block = self.next_block(other, ux);
+        }
+
+        self.upper.reuse_as(&other.upper);
+        self.min = other.min;
+        self.max = other.max;
+        self.len = other.len;
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        self.union_with(other);
+
+        // Only the marked blocks of other can have elements,
+        // so there is no need to clear the others.
+        let mut block = other.upper.first();
+        while let Some(ux) = block {
+            other.lower[ux].clear();
+            block = other.following_block(ux);
+        }
+        other.upper.clear();
+        other.set_empty();
+    }
+
+    pub fn shift_by(&self, offset: isize) -> Option<Self> {
+        if !offset.unsigned_abs().is_multiple_of(Lower::CAPACITY) {
+            return crate::shift_by_inserting(self, offset);
+        }
+        if self.is_empty() {
+            return Some(Self::new());
+        }
+        if !crate::shift_fits(self, offset) {
+            return None;
+        }
+
+        // Shifting by whole blocks moves the blocks as they are,
+        // without looking at the elements inside them.
+        let block_offset = offset >> Lower::BITS;
+        let mut result = Self::new();
+        result.upper = self
+            .upper
+            .shift_by(block_offset)
+            .expect("the blocks of elements that fit also fit");
+        let mut block = self.upper.first();
+        while let Some(ux) = block {
+            result.lower[ux.wrapping_add_signed(block_offset)] = self.lower[ux];
+            block = self.following_block(ux);
+        }
+        result.min = self.min.wrapping_add_signed(offset);
+        result.max = self.max.wrapping_add_signed(offset);
+        result.len = self.len;
+        Some(result)
+    }
+
+    pub fn from_sorted_shifted(xs: &[usize], offset: usize) -> Self {
+        let mut tree = Self::new();
+        let (Some(&first), Some(&last)) = (xs.first(), xs.last()) else {
+            return tree;
+        };
+        debug_assert!(
+            last - offset < Self::CAPACITY,
+            "{last} does not fit in the set."
+        );
+        tree.min = first - offset;
+        tree.max = last - offset;
+        tree.len = 1;
+
+        // The elements of each block are next to each other
+        // in the slice, and make the whole block at once.
+        let mut rest = &xs[xs.partition_point(|&x| x == first)..];
+        while let Some(&x) = rest.first() {
+            let ux = (x - offset) >> Lower::BITS;
+            let start = offset + (ux << Lower::BITS);
+            let end = rest.partition_point(|&y| y - start < Lower::CAPACITY);
+            tree.lower[ux] = Lower::from_sorted_shifted(&rest[..end], start);
+            tree.upper.insert(ux);
+            tree.len += tree.lower[ux].len();
+            rest = &rest[end..];
+        }
+        tree
+    }
+
+    pub fn from_bitmask(words: &[u64]) -> Self {
+        debug_assert_eq!(words.len(), Self::CAPACITY / 64);
+        debug_assert!(Lower::CAPACITY >= 64);
+
+        // Each block is made from its own words,
+        // and the empty blocks are skipped.
+        let mut tree = Self::new();
+        let mut lower_len = 0;
+        for (ux, block) in words.chunks_exact(Lower::CAPACITY / 64).enumerate() {
+            if block.iter().any(|&word| word != 0) {
+                tree.lower[ux] = Lower::from_bitmask(block);
+                tree.upper.insert(ux);
+                lower_len += tree.lower[ux].len();
+            }
+        }
+        tree.rebuild(lower_len);
+        tree
+    }
+
+    pub fn write_bitmask(&self, words: &mut [u64]) {
+        let block_words = Lower::CAPACITY / 64;
+        let mut block = self.upper.first();
+        while let Some(ux) = block {
+            self.lower[ux].write_bitmask(&mut words[ux * block_words..(ux + 1) * block_words]);
+            block = self.following_block(ux);
+        }
+
+        // The blocks may overwrite whole words,
+        // so min, which is not in them, goes last.
+        if let Some(min) = self.first() {
+            words[min >> 6] |= 1 << (min & 63);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = usize> + '_ {
+        // min is not in the blocks, so it is yielded on its own,
+        // and each task walks a single non-empty block.
+        let blocks = (0..UPPER_CAPACITY)
+            .into_par_iter()
+            .filter(move |&ux| self.upper.contains(ux))
+            .flat_map_iter(move |ux| {
+                self.lower[ux]
+                    .iter()
+                    .map(move |lx| (ux << Lower::BITS) + lx)
+            });
+        self.first().into_par_iter().chain(blocks)
+    }
+
     pub fn first(&self) -> Option<usize> {
         (!self.is_empty()).then_some(self.min)
     }
@@ -251,19 +1329,50 @@ where
         Self::capacity()
     }
 
-    #[cfg(feature = "dyn_capacity")]
-    fn init(value: &mut <Self as HasDeepMaybeUninit>::AsDeepMaybeUninit) {
-        Self::init(value);
+    fn bits(&self) -> usize {
+        <Self as InnerVEBTree>::BITS
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn core::any::Any {
+        self
+    }
+
+    fn as_dyn(&self) -> &dyn crate::VEBTree {
+        self
     }
 
     fn clear(&mut self) {
         self.clear();
     }
 
+    fn fill(&mut self) {
+        self.fill();
+    }
+
     fn is_empty(&self) -> bool {
         self.is_empty()
     }
 
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn count(&self) -> usize {
+        self.count()
+    }
+
+    fn rank(&self, x: usize) -> usize {
+        self.rank(x)
+    }
+
+    fn select(&self, k: usize) -> Option<usize> {
+        self.select(k)
+    }
+
     fn contains(&self, x: usize) -> bool {
         self.contains(x)
     }
@@ -272,10 +1381,38 @@ where
         self.insert(x)
     }
 
+    fn insert_and_return_neighbor(&mut self, x: usize) -> (bool, Option<usize>) {
+        self.insert_and_return_neighbor(x)
+    }
+
+    fn insert_range(&mut self, lo: usize, hi: usize) {
+        self.insert_range(lo, hi);
+    }
+
     fn remove(&mut self, x: usize) -> bool {
         self.remove(x)
     }
 
+    fn toggle(&mut self, x: usize) -> bool {
+        self.toggle(x)
+    }
+
+    fn remove_range(&mut self, lo: usize, hi: usize) {
+        self.remove_range(lo, hi);
+    }
+
+    fn toggle_range(&mut self, lo: usize, hi: usize) {
+        self.toggle_range(lo, hi);
+    }
+
+    fn pop_first(&mut self) -> Option<usize> {
+        self.pop_first()
+    }
+
+    fn pop_last(&mut self) -> Option<usize> {
+        self.pop_last()
+    }
+
     fn next(&self, x: usize) -> Option<usize> {
         self.next(x)
     }
@@ -284,6 +1421,10 @@ where
         self.prev(x)
     }
 
+    fn next_gap(&self, x: usize) -> Option<usize> {
+        self.next_gap(x)
+    }
+
     fn first(&self) -> Option<usize> {
         self.first()
     }
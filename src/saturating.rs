@@ -0,0 +1,135 @@
+use crate::InnerVEBTree;
+
+/// A wrapper around a `VEBTree` which never panics
+/// on integers that are too big for it.
+///
+/// Queries clamp their argument to `CAPACITY - 1`,
+/// and inserts of integers that are too big are ignored.
+/// This keeps a program going when some inputs overshoot,
+/// but the answers are only correct for integers in [0, CAPACITY).
+/// An ignored insert means the integer is silently missing
+/// from the set, so use the plain tree if that is a bug.
+///
+/// ```
+/// use flat_veb::{SaturatingVEBTree, SizedVEBTree};
+///
+/// let mut tree = SaturatingVEBTree::<SizedVEBTree<8>>::new();
+/// assert!(tree.insert(255));
+/// assert!(!tree.insert(1000));
+///
+/// // 1000 is clamped to 255, which is in the set.
+/// assert!(tree.contains(1000));
+/// assert_eq!(tree.prev(1000), Some(255));
+/// assert_eq!(tree.len(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SaturatingVEBTree<T: InnerVEBTree> {
+    tree: T,
+}
+
+impl<T: InnerVEBTree> SaturatingVEBTree<T> {
+    /// Makes an empty set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the set inside the wrapper.
+    #[must_use]
+    pub fn inner(&self) -> &T {
+        &self.tree
+    }
+
+    /// Returns the set inside the wrapper,
+    /// which panics on integers that are too big again.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.tree
+    }
+
+    /// Clamps x to the biggest integer the set can hold.
+    fn clamp(x: usize) -> usize {
+        x.min(T::CAPACITY - 1)
+    }
+
+    /// Returns the number of elements in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Clears the set, removing all elements.
+    pub fn clear(&mut self) {
+        self.tree.clear();
+    }
+
+    /// Returns true if the set contains x,
+    /// after clamping x to `CAPACITY - 1`.
+    #[must_use]
+    pub fn contains(&self, x: usize) -> bool {
+        self.tree.contains(Self::clamp(x))
+    }
+
+    /// Adds x to the set, if x is less than `CAPACITY`.
+    /// Bigger integers are ignored, and not clamped.
+    ///
+    /// If the set did not have x present and x was added,
+    /// true is returned.
+    pub fn insert(&mut self, x: usize) -> bool {
+        x < T::CAPACITY && self.tree.insert(x)
+    }
+
+    /// Removes x from the set, if x is less than `CAPACITY`.
+    /// Bigger integers are never in the set, so they are ignored.
+    ///
+    /// If the set did have x present, true is returned.
+    pub fn remove(&mut self, x: usize) -> bool {
+        x < T::CAPACITY && self.tree.remove(x)
+    }
+
+    /// Returns the first element in the set, if any.
+    #[must_use]
+    pub fn first(&self) -> Option<usize> {
+        self.tree.first()
+    }
+
+    /// Returns the last element in the set, if any.
+    #[must_use]
+    pub fn last(&self) -> Option<usize> {
+        self.tree.last()
+    }
+
+    /// Returns the smallest element in the set that is
+    /// greater or equal to x clamped to `CAPACITY - 1`, if any.
+    ///
+    /// For x at least `CAPACITY`,
+    /// this is `Some(CAPACITY - 1)` if the set contains it.
+    #[must_use]
+    pub fn next(&self, x: usize) -> Option<usize> {
+        self.tree.next(Self::clamp(x))
+    }
+
+    /// Returns the biggest element in the set that is
+    /// less or equal to x clamped to `CAPACITY - 1`, if any.
+    #[must_use]
+    pub fn prev(&self, x: usize) -> Option<usize> {
+        self.tree.prev(Self::clamp(x))
+    }
+
+    /// Returns an iterator over the values in the set.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = usize> + ExactSizeIterator + '_ {
+        self.tree.iter()
+    }
+}
+
+impl<T: InnerVEBTree> From<T> for SaturatingVEBTree<T> {
+    fn from(tree: T) -> Self {
+        Self { tree }
+    }
+}
@@ -0,0 +1,264 @@
+extern crate alloc;
+
+use crate::{
+    bit_array::BitArraySet,
+    outer,
+    private::ConditionalHasDeepMaybeUninit,
+    small_set::{Bits, SmallSet},
+    InnerVEBTree, VEBIterator, VEBTree,
+};
+use core::{fmt, marker::PhantomData};
+use serde::{
+    de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Unexpected, Visitor},
+    ser::{SerializeStruct, Serializer},
+    Deserialize, Serialize,
+};
+
+const FIELDS: &[&str] = &["bits", "elements"];
+
+/// Serializes the elements of a tree as a sorted sequence.
+struct Elements<'a>(&'a dyn VEBTree);
+
+impl<'a> Serialize for Elements<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(VEBIterator::new(self.0))
+    }
+}
+
+/// Serializes a tree as its number of bits
+/// and the sorted list of its elements,
+/// so the size of the output only depends
+/// on the number of elements.
+fn serialize_tree<S: Serializer>(tree: &dyn VEBTree, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut state = serializer.serialize_struct("VEBTree", FIELDS.len())?;
+    state.serialize_field("bits", &(tree.capacity().trailing_zeros() as usize))?;
+    state.serialize_field("elements", &Elements(tree))?;
+    state.end()
+}
+
+impl<const BITS: usize, T: Bits> Serialize for SmallSet<BITS, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_tree(self, serializer)
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> Serialize for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_tree(self, serializer)
+    }
+}
+
+impl<const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Serialize
+    for outer::VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_tree(self, serializer)
+    }
+}
+
+impl Serialize for dyn VEBTree {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_tree(self, serializer)
+    }
+}
+
+/// Something a serialized tree can be deserialized into.
+trait Target: Sized {
+    /// Makes an empty tree holding integers with `bits` bits,
+    /// if there is such a tree.
+    fn with_bits(bits: usize) -> Option<Self>;
+
+    fn tree(&mut self) -> &mut dyn VEBTree;
+}
+
+impl<T: InnerVEBTree> Target for T {
+    fn with_bits(bits: usize) -> Option<Self> {
+        (bits == T::BITS).then(T::default)
+    }
+
+    fn tree(&mut self) -> &mut dyn VEBTree {
+        self
+    }
+}
+
+#[cfg(feature = "dyn_capacity")]
+impl Target for alloc::boxed::Box<dyn VEBTree> {
+    fn with_bits(bits: usize) -> Option<Self> {
+        if bits > crate::dyn_capacity::MAX_BITS {
+            return None;
+        }
+        let tree = crate::new_with_bits(bits);
+        (tree.capacity() == 1 << bits).then_some(tree)
+    }
+
+    fn tree(&mut self) -> &mut dyn VEBTree {
+        &mut **self
+    }
+}
+
+enum Field {
+    Bits,
+    Elements,
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldVisitor;
+
+        impl<'de> Visitor<'de> for FieldVisitor {
+            type Value = Field;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("`bits` or `elements`")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Field, E> {
+                match value {
+                    "bits" => Ok(Field::Bits),
+                    "elements" => Ok(Field::Elements),
+                    _ => Err(de::Error::unknown_field(value, FIELDS)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(FieldVisitor)
+    }
+}
+
+/// Inserts a sequence of elements into an existing tree,
+/// rejecting elements that don't fit instead of panicking.
+struct ElementsSeed<'a>(&'a mut dyn VEBTree);
+
+impl<'de, 'a> DeserializeSeed<'de> for ElementsSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for ElementsSeed<'a> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a sequence of integers smaller than {}",
+            self.0.capacity()
+        )
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(x) = seq.next_element::<usize>()? {
+            if x >= self.0.capacity() {
+                return Err(de::Error::invalid_value(
+                    Unexpected::Unsigned(x as u64),
+                    &"an element smaller than the capacity",
+                ));
+            }
+            self.0.insert(x);
+        }
+        Ok(())
+    }
+}
+
+struct TreeVisitor<T>(PhantomData<T>);
+
+impl<T: Target> TreeVisitor<T> {
+    fn make<E: de::Error>(&self, bits: usize) -> Result<T, E> {
+        T::with_bits(bits)
+            .ok_or_else(|| de::Error::invalid_value(Unexpected::Unsigned(bits as u64), self))
+    }
+}
+
+impl<'de, T: Target> Visitor<'de> for TreeVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a VEBTree with a number of bits this type can hold")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let bits = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let mut tree = self.make(bits)?;
+        seq.next_element_seed(ElementsSeed(tree.tree()))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(tree)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let mut tree = None;
+        let mut has_elements = false;
+
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Bits => {
+                    if tree.is_some() {
+                        return Err(de::Error::duplicate_field("bits"));
+                    }
+                    tree = Some(self.make(map.next_value()?)?);
+                }
+                Field::Elements => {
+                    if has_elements {
+                        return Err(de::Error::duplicate_field("elements"));
+                    }
+                    let Some(tree) = tree.as_mut() else {
+                        return Err(de::Error::custom("`bits` has to come before `elements`"));
+                    };
+                    map.next_value_seed(ElementsSeed(tree.tree()))?;
+                    has_elements = true;
+                }
+            }
+        }
+
+        if !has_elements {
+            return Err(de::Error::missing_field("elements"));
+        }
+        tree.ok_or_else(|| de::Error::missing_field("bits"))
+    }
+}
+
+fn deserialize_tree<'de, T: Target, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    deserializer.deserialize_struct("VEBTree", FIELDS, TreeVisitor(PhantomData))
+}
+
+impl<'de, const BITS: usize, T: Bits> Deserialize<'de> for SmallSet<BITS, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_tree(deserializer)
+    }
+}
+
+impl<'de, const BITS: usize, const WORDS: usize> Deserialize<'de> for BitArraySet<BITS, WORDS>
+where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_tree(deserializer)
+    }
+}
+
+impl<'de, const UPPER_CAPACITY: usize, Upper: InnerVEBTree, Lower: InnerVEBTree> Deserialize<'de>
+    for outer::VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_tree(deserializer)
+    }
+}
+
+/// Deserializes a tree with the capacity it was serialized with,
+/// using `new_with_bits`.
+#[cfg(feature = "dyn_capacity")]
+impl<'de> Deserialize<'de> for alloc::boxed::Box<dyn VEBTree> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_tree(deserializer)
+    }
+}
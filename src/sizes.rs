@@ -1,5 +1,5 @@
 extern crate alloc;
-use crate::{outer, small_set::SmallSet, InnerVEBTree};
+use crate::{bit_array::BitArraySet, outer, small_set::SmallSet, InnerVEBTree};
 
 /// Trait used as a function taking the integer `BITS`
 /// as an argument, returning a `VEBTree` holding integers
@@ -10,6 +10,18 @@ pub trait GetVEBTreeSize<const BITS: usize> {
     type Type: InnerVEBTree;
 }
 
+impl GetVEBTreeSize<0> for () {
+    type Type = SmallSet<0, u8>;
+}
+impl GetVEBTreeSize<1> for () {
+    type Type = SmallSet<1, u8>;
+}
+impl GetVEBTreeSize<2> for () {
+    type Type = SmallSet<2, u8>;
+}
+impl GetVEBTreeSize<3> for () {
+    type Type = SmallSet<3, u8>;
+}
 impl GetVEBTreeSize<4> for () {
     type Type = SmallSet<4, u16>;
 }
@@ -23,6 +35,24 @@ impl GetVEBTreeSize<7> for () {
     type Type = SmallSet<7, u128>;
 }
 
+// Up to 4096 elements, a flat array of words is faster
+// than recursing further down to the small sets.
+impl GetVEBTreeSize<8> for () {
+    type Type = BitArraySet<8, 4>;
+}
+impl GetVEBTreeSize<9> for () {
+    type Type = BitArraySet<9, 8>;
+}
+impl GetVEBTreeSize<10> for () {
+    type Type = BitArraySet<10, 16>;
+}
+impl GetVEBTreeSize<11> for () {
+    type Type = BitArraySet<11, 32>;
+}
+impl GetVEBTreeSize<12> for () {
+    type Type = BitArraySet<12, 64>;
+}
+
 macro_rules! make_veb_tree_sizes {
     ($n:expr, T T T T T T T T $($tail:tt)*) => {
         impl GetVEBTreeSize<{ $n }> for () {
@@ -37,8 +67,8 @@ macro_rules! make_veb_tree_sizes {
     ($n:expr, T T T T T T T) => {}
 }
 
-make_veb_tree_sizes! {8,
-    T T T T T T T T
+make_veb_tree_sizes! {13,
+    T T T
     T T T T T T T T
     T T T T T T T T
     T T T T T T T T
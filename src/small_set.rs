@@ -1,11 +1,12 @@
 use crate::{
     private::{ConditionalHasDeepMaybeUninit, Sealed},
-    InnerVEBTree, VEBTree,
+    InnerVEBTree, IntoIter, VEBIterator, VEBTree,
 };
-use core::ops::{BitAnd, BitOr, Not, Shl, Shr, Sub};
+use core::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr, Sub};
 #[cfg(feature = "dyn_capacity")]
-use deep_maybe_uninit::DeepMaybeUninit;
-use deep_maybe_uninit::HasDeepMaybeUninit;
+use deep_maybe_uninit::{DeepMaybeUninit, HasDeepMaybeUninit};
+#[cfg(feature = "rayon")]
+use rayon::iter::ParallelIterator;
 
 pub trait Bits:
     Copy
@@ -13,23 +14,38 @@ pub trait Bits:
     + Eq
     + BitAnd<Output = Self>
     + BitOr<Output = Self>
+    + BitXor<Output = Self>
     + Not<Output = Self>
     + Shl<usize, Output = Self>
     + Shr<usize, Output = Self>
     + Sub<Output = Self>
+    + Send
+    + Sync
+    + 'static
     + Sealed
     + ConditionalHasDeepMaybeUninit
 {
+    /// Number of bits in the type.
+    const WIDTH: usize;
     fn zero() -> Self;
     fn one() -> Self;
     fn leading_zeros(self) -> usize;
     fn trailing_zeros(self) -> usize;
+    fn count_ones(self) -> usize;
+    /// Returns a value with the n lowest bits set.
+    fn low_bits(n: usize) -> Self;
+    /// Reads the bits from words in little endian order,
+    /// where bit i of the value is bit i % 64 of word i / 64.
+    fn from_words(words: &[u64]) -> Self;
+    /// Writes the bits to words in the layout read by `from_words`.
+    fn write_words(self, words: &mut [u64]);
 }
 
 macro_rules! impl_bits {
     ($type:ty) => {
         impl Sealed for $type {}
         impl Bits for $type {
+            const WIDTH: usize = <$type>::BITS as usize;
             fn zero() -> Self {
                 0
             }
@@ -42,10 +58,34 @@ macro_rules! impl_bits {
             fn trailing_zeros(self) -> usize {
                 self.trailing_zeros() as usize
             }
+            fn count_ones(self) -> usize {
+                self.count_ones() as usize
+            }
+            fn low_bits(n: usize) -> Self {
+                if n >= <$type>::BITS as usize {
+                    !0
+                } else {
+                    (1 << n) - 1
+                }
+            }
+            #[allow(clippy::cast_possible_truncation, clippy::cast_lossless)]
+            fn from_words(words: &[u64]) -> Self {
+                words
+                    .iter()
+                    .enumerate()
+                    .fold(0, |bits, (w, &word)| bits | (word as Self) << (w << 6))
+            }
+            #[allow(clippy::cast_possible_truncation, clippy::cast_lossless)]
+            fn write_words(self, words: &mut [u64]) {
+                for (w, word) in words.iter_mut().enumerate() {
+                    *word = (self >> (w << 6)) as u64;
+                }
+            }
         }
     };
 }
 
+impl_bits!(u8);
 impl_bits!(u16);
 impl_bits!(u32);
 impl_bits!(u64);
@@ -53,7 +93,7 @@ impl_bits!(u128);
 
 /// Base case implementation of `VEBTree` for small integers.
 /// Maintains a set of integers from
-/// 0 to (exclusive) `1 << BITS = size_of::<T>() * 8`.
+/// 0 to (exclusive) `1 << BITS <= size_of::<T>() * 8`,
 /// using `T` as a collection of flags.
 #[cfg_attr(feature = "dyn_capacity", derive(DeepMaybeUninit))]
 #[derive(Clone, Copy)]
@@ -76,17 +116,90 @@ impl<const BITS: usize, T: Bits> Default for SmallSet<BITS, T> {
     }
 }
 
+impl<const BITS: usize, T: Bits> PartialEq for SmallSet<BITS, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<const BITS: usize, T: Bits> Eq for SmallSet<BITS, T> {}
+
+impl<const BITS: usize, T: Bits> core::hash::Hash for SmallSet<BITS, T> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for x in self.iter() {
+            x.hash(state);
+        }
+    }
+}
+
 impl<const BITS: usize, T: Bits> core::fmt::Debug for SmallSet<BITS, T> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_set().entries(self.iter()).finish()
     }
 }
 
+impl<const BITS: usize, T: Bits> core::fmt::Display for SmallSet<BITS, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        crate::fmt_runs(self, f)
+    }
+}
+
+impl<const BITS: usize, T: Bits> FromIterator<usize> for SmallSet<BITS, T> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<const BITS: usize, T: Bits> Extend<usize> for SmallSet<BITS, T> {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
+
+impl<'a, const BITS: usize, T: Bits> Extend<&'a usize> for SmallSet<BITS, T> {
+    fn extend<I: IntoIterator<Item = &'a usize>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, const BITS: usize, T: Bits> IntoIterator for &'a SmallSet<BITS, T> {
+    type Item = usize;
+    type IntoIter = VEBIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const BITS: usize, T: Bits> IntoIterator for SmallSet<BITS, T> {
+    type Item = usize;
+    type IntoIter = IntoIter<Self>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
 impl<const BITS: usize, T: Bits> SmallSet<BITS, T> {
     pub fn capacity() -> usize {
         Self::CAPACITY
     }
 
+    /// Returns the flags for the elements in [lo, hi),
+    /// with hi clamped to the capacity.
+    fn range_mask(lo: usize, hi: usize) -> T {
+        let hi = hi.min(Self::CAPACITY);
+        if lo >= hi {
+            return T::zero();
+        }
+        T::low_bits(hi) & !T::low_bits(lo)
+    }
+
     #[cfg(feature = "dyn_capacity")]
     pub fn init(value: &mut <Self as HasDeepMaybeUninit>::AsDeepMaybeUninit) {
         value.bits = T::zero().forget_init();
@@ -96,13 +209,43 @@ impl<const BITS: usize, T: Bits> SmallSet<BITS, T> {
         self.bits = T::zero();
     }
 
+    pub fn fill(&mut self) {
+        self.bits = T::low_bits(Self::CAPACITY);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.bits == T::zero()
     }
 
+    pub fn len(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    pub fn count(&self) -> usize {
+        self.len()
+    }
+
+    pub fn rank(&self, x: usize) -> usize {
+        if x >= Self::CAPACITY {
+            return self.len();
+        }
+        (self.bits & ((T::one() << x) - T::one())).count_ones()
+    }
+
+    pub fn select(&self, k: usize) -> Option<usize> {
+        if k >= self.len() {
+            return None;
+        }
+        let mut bits = self.bits;
+        for _ in 0..k {
+            bits = bits & (bits - T::one());
+        }
+        Some(bits.trailing_zeros())
+    }
+
     pub fn contains(&self, x: usize) -> bool {
         debug_assert!(x < Self::CAPACITY);
-        self.bits >> x & T::one() != T::zero()
+        x < Self::CAPACITY && self.bits >> x & T::one() != T::zero()
     }
 
     pub fn insert(&mut self, x: usize) -> bool {
@@ -111,26 +254,180 @@ impl<const BITS: usize, T: Bits> SmallSet<BITS, T> {
         !was
     }
 
+    pub fn insert_and_return_neighbor(&mut self, x: usize) -> (bool, Option<usize>) {
+        let successor = if x + 1 < Self::CAPACITY {
+            self.next(x + 1)
+        } else {
+            None
+        };
+        (self.insert(x), successor)
+    }
+
+    pub fn insert_range(&mut self, lo: usize, hi: usize) {
+        self.bits = self.bits | Self::range_mask(lo, hi);
+    }
+
     pub fn remove(&mut self, x: usize) -> bool {
         let was = self.contains(x);
         self.bits = self.bits & !(T::one() << x);
         was
     }
 
+    pub fn toggle(&mut self, x: usize) -> bool {
+        debug_assert!(x < Self::CAPACITY);
+        self.bits = self.bits ^ T::one() << x;
+        self.contains(x)
+    }
+
+    pub fn pop_first(&mut self) -> Option<usize> {
+        let x = self.first()?;
+        self.remove(x);
+        Some(x)
+    }
+
+    pub fn pop_last(&mut self) -> Option<usize> {
+        let x = self.last()?;
+        self.remove(x);
+        Some(x)
+    }
+
+    pub fn remove_range(&mut self, lo: usize, hi: usize) {
+        self.bits = self.bits & !Self::range_mask(lo, hi);
+    }
+
+    pub fn toggle_range(&mut self, lo: usize, hi: usize) {
+        self.bits = self.bits ^ Self::range_mask(lo, hi);
+    }
+
     pub fn next(&self, x: usize) -> Option<usize> {
         debug_assert!(x < Self::CAPACITY);
+        if x >= Self::CAPACITY {
+            return None;
+        }
         let big_enough = self.bits & !((T::one() << x) - T::one());
         (big_enough != T::zero()).then(|| big_enough.trailing_zeros())
     }
 
     pub fn prev(&self, x: usize) -> Option<usize> {
         debug_assert!(x < Self::CAPACITY);
+        if x >= Self::CAPACITY {
+            return None;
+        }
         let small_enough = if x == Self::CAPACITY - 1 {
             self.bits
         } else {
             self.bits & ((T::one() << (x + 1)) - T::one())
         };
-        (small_enough != T::zero()).then(|| Self::CAPACITY - 1 - small_enough.leading_zeros())
+        (small_enough != T::zero()).then(|| T::WIDTH - 1 - small_enough.leading_zeros())
+    }
+
+    pub fn next_gap(&self, x: usize) -> Option<usize> {
+        debug_assert!(x < Self::CAPACITY);
+        let missing = !self.bits & !((T::one() << x) - T::one());
+        let gap = missing.trailing_zeros();
+        (missing != T::zero() && gap < Self::CAPACITY).then_some(gap)
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        self.bits = self.bits | other.bits;
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.bits = self.bits & other.bits;
+    }
+
+    pub fn difference_with(&mut self, other: &Self) {
+        self.bits = self.bits & !other.bits;
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &Self) {
+        self.bits = self.bits ^ other.bits;
+    }
+
+    pub fn diff(&self, other: &Self) -> (Self, Self) {
+        (
+            Self {
+                bits: self.bits & !other.bits,
+            },
+            Self {
+                bits: other.bits & !self.bits,
+            },
+        )
+    }
+
+    pub fn complement(&self) -> Self {
+        Self {
+            bits: !self.bits & T::low_bits(Self::CAPACITY),
+        }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.bits & !other.bits == T::zero()
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.bits & other.bits == T::zero()
+    }
+
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        (self.bits & other.bits).count_ones()
+    }
+
+    pub fn split_off(&mut self, x: usize) -> Self {
+        let mut other = *self;
+        self.remove_range(x, Self::CAPACITY);
+        other.remove_range(0, x);
+        other
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        self.union_with(other);
+        other.clear();
+    }
+
+    pub fn shift_by(&self, offset: isize) -> Option<Self> {
+        if self.is_empty() {
+            return Some(Self::new());
+        }
+        if !crate::shift_fits(self, offset) {
+            return None;
+        }
+        // The shift is less than the capacity, so it is in range for T.
+        let bits = if offset >= 0 {
+            self.bits << offset.unsigned_abs()
+        } else {
+            self.bits >> offset.unsigned_abs()
+        };
+        Some(Self { bits })
+    }
+
+    pub fn from_sorted_shifted(xs: &[usize], offset: usize) -> Self {
+        let mut set = Self::new();
+        for &x in xs {
+            debug_assert!(x - offset < Self::CAPACITY, "{x} does not fit in the set.");
+            set.bits = set.bits | T::one() << (x - offset);
+        }
+        set
+    }
+
+    pub fn from_bitmask(words: &[u64]) -> Self {
+        debug_assert_eq!(words.len(), Self::CAPACITY.div_ceil(64));
+        let bits = T::from_words(words);
+        debug_assert!(
+            bits & !T::low_bits(Self::CAPACITY) == T::zero(),
+            "Bits after the capacity are set."
+        );
+        Self { bits }
+    }
+
+    pub fn write_bitmask(&self, words: &mut [u64]) {
+        self.bits.write_words(words);
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = usize> + '_ {
+        // Too small to be worth splitting up.
+        rayon::iter::once(self).flat_map_iter(|set| set.iter())
     }
 
     pub fn first(&self) -> Option<usize> {
@@ -138,12 +435,83 @@ impl<const BITS: usize, T: Bits> SmallSet<BITS, T> {
     }
 
     pub fn last(&self) -> Option<usize> {
-        (self.bits != T::zero()).then(|| Self::CAPACITY - 1 - self.bits.leading_zeros())
+        (self.bits != T::zero()).then(|| T::WIDTH - 1 - self.bits.leading_zeros())
     }
 }
 
 impl<const BITS: usize, T: Bits> InnerVEBTree for SmallSet<BITS, T> {
     const BITS: usize = BITS;
+    const HEIGHT: usize = 0;
+
+    #[cfg(feature = "dyn_capacity")]
+    fn init(value: &mut <Self as HasDeepMaybeUninit>::AsDeepMaybeUninit) {
+        Self::init(value);
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        self.union_with(other);
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        self.intersect_with(other);
+    }
+
+    fn difference_with(&mut self, other: &Self) {
+        self.difference_with(other);
+    }
+
+    fn symmetric_difference_with(&mut self, other: &Self) {
+        self.symmetric_difference_with(other);
+    }
+
+    fn diff(&self, other: &Self) -> (Self, Self) {
+        self.diff(other)
+    }
+
+    fn complement(&self) -> Self {
+        self.complement()
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.is_subset(other)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.is_disjoint(other)
+    }
+
+    fn intersection_len(&self, other: &Self) -> usize {
+        self.intersection_len(other)
+    }
+
+    fn split_off(&mut self, x: usize) -> Self {
+        self.split_off(x)
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.append(other);
+    }
+
+    fn shift_by(&self, offset: isize) -> Option<Self> {
+        self.shift_by(offset)
+    }
+
+    fn from_sorted_shifted(xs: &[usize], offset: usize) -> Self {
+        Self::from_sorted_shifted(xs, offset)
+    }
+
+    fn from_bitmask(words: &[u64]) -> Self {
+        Self::from_bitmask(words)
+    }
+
+    fn write_bitmask(&self, words: &mut [u64]) {
+        self.write_bitmask(words);
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_iter(&self) -> impl ParallelIterator<Item = usize> + '_ {
+        self.par_iter()
+    }
 }
 
 impl<const BITS: usize, T: Bits> VEBTree for SmallSet<BITS, T> {
@@ -151,19 +519,50 @@ impl<const BITS: usize, T: Bits> VEBTree for SmallSet<BITS, T> {
         Self::capacity()
     }
 
-    #[cfg(feature = "dyn_capacity")]
-    fn init(value: &mut <Self as HasDeepMaybeUninit>::AsDeepMaybeUninit) {
-        Self::init(value);
+    fn bits(&self) -> usize {
+        <Self as InnerVEBTree>::BITS
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn core::any::Any {
+        self
+    }
+
+    fn as_dyn(&self) -> &dyn VEBTree {
+        self
     }
 
     fn clear(&mut self) {
         self.clear();
     }
 
+    fn fill(&mut self) {
+        self.fill();
+    }
+
     fn is_empty(&self) -> bool {
         self.is_empty()
     }
 
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn count(&self) -> usize {
+        self.count()
+    }
+
+    fn rank(&self, x: usize) -> usize {
+        self.rank(x)
+    }
+
+    fn select(&self, k: usize) -> Option<usize> {
+        self.select(k)
+    }
+
     fn contains(&self, x: usize) -> bool {
         self.contains(x)
     }
@@ -172,10 +571,38 @@ impl<const BITS: usize, T: Bits> VEBTree for SmallSet<BITS, T> {
         self.insert(x)
     }
 
+    fn insert_and_return_neighbor(&mut self, x: usize) -> (bool, Option<usize>) {
+        self.insert_and_return_neighbor(x)
+    }
+
+    fn insert_range(&mut self, lo: usize, hi: usize) {
+        self.insert_range(lo, hi);
+    }
+
     fn remove(&mut self, x: usize) -> bool {
         self.remove(x)
     }
 
+    fn toggle(&mut self, x: usize) -> bool {
+        self.toggle(x)
+    }
+
+    fn remove_range(&mut self, lo: usize, hi: usize) {
+        self.remove_range(lo, hi);
+    }
+
+    fn toggle_range(&mut self, lo: usize, hi: usize) {
+        self.toggle_range(lo, hi);
+    }
+
+    fn pop_first(&mut self) -> Option<usize> {
+        self.pop_first()
+    }
+
+    fn pop_last(&mut self) -> Option<usize> {
+        self.pop_last()
+    }
+
     fn next(&self, x: usize) -> Option<usize> {
         self.next(x)
     }
@@ -184,6 +611,10 @@ impl<const BITS: usize, T: Bits> VEBTree for SmallSet<BITS, T> {
         self.prev(x)
     }
 
+    fn next_gap(&self, x: usize) -> Option<usize> {
+        self.next_gap(x)
+    }
+
     fn first(&self) -> Option<usize> {
         self.first()
     }
@@ -0,0 +1,183 @@
+use crate::{private::Sealed, SizedVEBTree, VEBTree};
+
+/// A `VEBTree` with capacity chosen at run time,
+/// without heap allocation.
+///
+/// Unlike `new_with_capacity`, which boxes the tree,
+/// this is a plain value holding one of a few fixed sizes,
+/// so it can be stored on the stack or in a static.
+/// It takes as much space as the largest variant,
+/// which is a bit over 128 KB.
+///
+/// ```
+/// use flat_veb::{VEBTree, VEBTreeEnum};
+///
+/// let mut tree = VEBTreeEnum::with_bits(10);
+/// assert_eq!(tree.capacity(), 1 << 12);
+/// assert!(tree.insert(1000));
+/// assert_eq!(tree.next(42), Some(1000));
+/// ```
+// The variants are stored inline on purpose, boxing them
+// would make this the same as `new_with_capacity`.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VEBTreeEnum {
+    /// A set holding integers with 8 bits.
+    Bits8(SizedVEBTree<8>),
+    /// A set holding integers with 12 bits.
+    Bits12(SizedVEBTree<12>),
+    /// A set holding integers with 16 bits.
+    Bits16(SizedVEBTree<16>),
+    /// A set holding integers with 20 bits.
+    Bits20(SizedVEBTree<20>),
+}
+
+impl VEBTreeEnum {
+    /// The highest number of bits any variant can hold.
+    pub const MAX_BITS: usize = 20;
+
+    /// Makes an empty set of the smallest variant
+    /// holding integers with at least `bits` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than `MAX_BITS`.
+    #[must_use]
+    pub fn with_bits(bits: usize) -> Self {
+        match bits {
+            0..=8 => Self::Bits8(SizedVEBTree::<8>::new()),
+            9..=12 => Self::Bits12(SizedVEBTree::<12>::new()),
+            13..=16 => Self::Bits16(SizedVEBTree::<16>::new()),
+            17..=20 => Self::Bits20(SizedVEBTree::<20>::new()),
+            _ => panic!(
+                "Too high number of bits: {bits}. The highest supported is {}.",
+                Self::MAX_BITS
+            ),
+        }
+    }
+}
+
+impl Sealed for VEBTreeEnum {}
+
+/// Calls the same method on whichever variant the set is.
+macro_rules! dispatch {
+    ($self:ident, $tree:ident => $e:expr) => {
+        match $self {
+            VEBTreeEnum::Bits8($tree) => $e,
+            VEBTreeEnum::Bits12($tree) => $e,
+            VEBTreeEnum::Bits16($tree) => $e,
+            VEBTreeEnum::Bits20($tree) => $e,
+        }
+    };
+}
+
+impl VEBTree for VEBTreeEnum {
+    fn capacity(&self) -> usize {
+        dispatch!(self, tree => tree.capacity())
+    }
+
+    fn bits(&self) -> usize {
+        dispatch!(self, tree => tree.bits())
+    }
+
+    /// Returns the enum itself, not the variant inside it.
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn core::any::Any {
+        self
+    }
+
+    fn as_dyn(&self) -> &dyn VEBTree {
+        self
+    }
+
+    fn clear(&mut self) {
+        dispatch!(self, tree => tree.clear());
+    }
+
+    fn fill(&mut self) {
+        dispatch!(self, tree => tree.fill());
+    }
+
+    fn is_empty(&self) -> bool {
+        dispatch!(self, tree => tree.is_empty())
+    }
+
+    fn len(&self) -> usize {
+        dispatch!(self, tree => tree.len())
+    }
+
+    fn count(&self) -> usize {
+        dispatch!(self, tree => tree.count())
+    }
+
+    fn rank(&self, x: usize) -> usize {
+        dispatch!(self, tree => tree.rank(x))
+    }
+
+    fn select(&self, k: usize) -> Option<usize> {
+        dispatch!(self, tree => tree.select(k))
+    }
+
+    fn contains(&self, x: usize) -> bool {
+        dispatch!(self, tree => tree.contains(x))
+    }
+
+    fn insert(&mut self, x: usize) -> bool {
+        dispatch!(self, tree => tree.insert(x))
+    }
+
+    fn insert_and_return_neighbor(&mut self, x: usize) -> (bool, Option<usize>) {
+        dispatch!(self, tree => tree.insert_and_return_neighbor(x))
+    }
+
+    fn insert_range(&mut self, lo: usize, hi: usize) {
+        dispatch!(self, tree => tree.insert_range(lo, hi));
+    }
+
+    fn remove(&mut self, x: usize) -> bool {
+        dispatch!(self, tree => tree.remove(x))
+    }
+
+    fn toggle(&mut self, x: usize) -> bool {
+        dispatch!(self, tree => tree.toggle(x))
+    }
+
+    fn remove_range(&mut self, lo: usize, hi: usize) {
+        dispatch!(self, tree => tree.remove_range(lo, hi));
+    }
+
+    fn toggle_range(&mut self, lo: usize, hi: usize) {
+        dispatch!(self, tree => tree.toggle_range(lo, hi));
+    }
+
+    fn pop_first(&mut self) -> Option<usize> {
+        dispatch!(self, tree => tree.pop_first())
+    }
+
+    fn pop_last(&mut self) -> Option<usize> {
+        dispatch!(self, tree => tree.pop_last())
+    }
+
+    fn next(&self, x: usize) -> Option<usize> {
+        dispatch!(self, tree => tree.next(x))
+    }
+
+    fn prev(&self, x: usize) -> Option<usize> {
+        dispatch!(self, tree => tree.prev(x))
+    }
+
+    fn next_gap(&self, x: usize) -> Option<usize> {
+        dispatch!(self, tree => tree.next_gap(x))
+    }
+
+    fn first(&self) -> Option<usize> {
+        dispatch!(self, tree => tree.first())
+    }
+
+    fn last(&self) -> Option<usize> {
+        dispatch!(self, tree => tree.last())
+    }
+}
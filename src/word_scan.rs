@@ -0,0 +1,116 @@
+//! Scans over the word arrays of `BitArraySet`.
+//!
+//! With the `simd` feature and AVX2 enabled at compile time,
+//! for example with `-C target-cpu=native`, four words are checked at a time.
+//! Otherwise, and on other targets, the words are checked one by one.
+//! The check has to happen at compile time since there is no
+//! runtime feature detection without `std`.
+
+#[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "avx2"))]
+pub use avx2::{count_ones, position_nonzero, rposition_nonzero};
+#[cfg(not(all(feature = "simd", target_arch = "x86_64", target_feature = "avx2")))]
+pub use scalar::{count_ones, position_nonzero, rposition_nonzero};
+
+mod scalar {
+    /// Returns the index of the first non-zero word, if any.
+    pub fn position_nonzero(words: &[u64]) -> Option<usize> {
+        words.iter().position(|&word| word != 0)
+    }
+
+    /// Returns the index of the last non-zero word, if any.
+    pub fn rposition_nonzero(words: &[u64]) -> Option<usize> {
+        words.iter().rposition(|&word| word != 0)
+    }
+
+    /// Returns the number of set bits in the words.
+    pub fn count_ones(words: &[u64]) -> usize {
+        words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "avx2"))]
+mod avx2 {
+    use super::scalar;
+    use core::arch::x86_64::{
+        __m256i, _mm256_add_epi64, _mm256_add_epi8, _mm256_and_si256, _mm256_loadu_si256,
+        _mm256_sad_epu8, _mm256_set1_epi8, _mm256_setr_epi8, _mm256_setzero_si256,
+        _mm256_shuffle_epi8, _mm256_srli_epi16, _mm256_storeu_si256, _mm256_testz_si256,
+    };
+
+    /// Returns true if all of the four words are zero.
+    ///
+    /// The load is unaligned, so the pointer cast is fine
+    /// even though `u64` is less aligned than `__m256i`.
+    #[allow(clippy::cast_ptr_alignment)]
+    fn is_zero(chunk: &[u64]) -> bool {
+        debug_assert_eq!(chunk.len(), 4);
+        // SAFETY: AVX2 is enabled at compile time,
+        // and the chunk has the 32 bytes that are read.
+        unsafe {
+            let v = _mm256_loadu_si256(chunk.as_ptr().cast::<__m256i>());
+            _mm256_testz_si256(v, v) == 1
+        }
+    }
+
+    pub fn position_nonzero(words: &[u64]) -> Option<usize> {
+        let chunks = words.chunks_exact(4);
+        let rest = chunks.remainder();
+        for (i, chunk) in chunks.enumerate() {
+            if !is_zero(chunk) {
+                return scalar::position_nonzero(chunk).map(|w| 4 * i + w);
+            }
+        }
+        let rest_start = words.len() - rest.len();
+        scalar::position_nonzero(rest).map(|w| rest_start + w)
+    }
+
+    pub fn rposition_nonzero(words: &[u64]) -> Option<usize> {
+        let chunks = words.rchunks_exact(4);
+        let rest = chunks.remainder();
+        for (i, chunk) in chunks.enumerate() {
+            if !is_zero(chunk) {
+                let start = words.len() - 4 * (i + 1);
+                return scalar::rposition_nonzero(chunk).map(|w| start + w);
+            }
+        }
+        scalar::rposition_nonzero(rest)
+    }
+
+    /// Counts the bits of each nibble with a lookup table,
+    /// and sums the counts of each 64 bit lane.
+    ///
+    /// Like in `is_zero`, the loads and the store are unaligned,
+    /// so the pointer casts are fine.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_ptr_alignment)]
+    pub fn count_ones(words: &[u64]) -> usize {
+        let chunks = words.chunks_exact(4);
+        let rest = chunks.remainder();
+
+        // SAFETY: AVX2 is enabled at compile time,
+        // and every chunk has the 32 bytes that are read.
+        let lanes = unsafe {
+            let lookup = _mm256_setr_epi8(
+                0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4, //
+                0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+            );
+            let low_nibbles = _mm256_set1_epi8(0x0f);
+            let mut total = _mm256_setzero_si256();
+            for chunk in chunks {
+                let v = _mm256_loadu_si256(chunk.as_ptr().cast::<__m256i>());
+                let lo = _mm256_and_si256(v, low_nibbles);
+                let hi = _mm256_and_si256(_mm256_srli_epi16::<4>(v), low_nibbles);
+                let counts = _mm256_add_epi8(
+                    _mm256_shuffle_epi8(lookup, lo),
+                    _mm256_shuffle_epi8(lookup, hi),
+                );
+                total = _mm256_add_epi64(total, _mm256_sad_epu8(counts, _mm256_setzero_si256()));
+            }
+            let mut lanes = [0u64; 4];
+            _mm256_storeu_si256(lanes.as_mut_ptr().cast::<__m256i>(), total);
+            lanes
+        };
+
+        // The lanes count at most 64 bits per word.
+        lanes.iter().sum::<u64>() as usize + scalar::count_ones(rest)
+    }
+}
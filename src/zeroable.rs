@@ -0,0 +1,29 @@
+use crate::{
+    bit_array::BitArraySet,
+    outer,
+    private::ConditionalHasDeepMaybeUninit,
+    small_set::{Bits, SmallSet},
+    InnerVEBTree,
+};
+use bytemuck::Zeroable;
+
+// SAFETY: `SmallSet` is `repr(C)` and only contains a `T`,
+// which is a primitive integer.
+unsafe impl<const BITS: usize, T: Bits + Zeroable> Zeroable for SmallSet<BITS, T> {}
+
+// SAFETY: `BitArraySet` is `repr(C)` and only contains an array of `u64`.
+unsafe impl<const BITS: usize, const WORDS: usize> Zeroable for BitArraySet<BITS, WORDS> where
+    [(); WORDS]: ConditionalHasDeepMaybeUninit
+{
+}
+
+// SAFETY: `outer::VEBTree` is `repr(C)` and only contains
+// `usize`s and trees that are themselves `Zeroable`.
+unsafe impl<const UPPER_CAPACITY: usize, Upper, Lower> Zeroable
+    for outer::VEBTree<UPPER_CAPACITY, Upper, Lower>
+where
+    Upper: InnerVEBTree + Zeroable,
+    Lower: InnerVEBTree + Zeroable,
+    [(); UPPER_CAPACITY]: ConditionalHasDeepMaybeUninit,
+{
+}
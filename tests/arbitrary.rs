@@ -0,0 +1,37 @@
+#![cfg(feature = "arbitrary")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use flat_veb::{InnerVEBTree, SizedVEBTree};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+
+fn check_consistent<T: InnerVEBTree + for<'a> Arbitrary<'a>>(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..50 {
+        let len = rng.gen_range(0..4096);
+        let data = (0..len).map(|_| rng.gen()).collect::<Vec<u8>>();
+        let tree = T::arbitrary(&mut Unstructured::new(&data)).unwrap();
+
+        assert_eq!(tree.iter().count(), tree.len());
+        assert!(tree.len() <= 1024);
+        assert!(tree.iter().all(|x| x < T::CAPACITY));
+        match (tree.first(), tree.last()) {
+            (Some(first), Some(last)) => assert!(first <= last),
+            (None, None) => assert!(tree.is_empty()),
+            _ => panic!("only one of first and last is None"),
+        }
+    }
+}
+
+#[test]
+fn arbitrary_trees_are_consistent() {
+    check_consistent::<SizedVEBTree<4>>(4);
+    check_consistent::<SizedVEBTree<10>>(10);
+    check_consistent::<SizedVEBTree<16>>(16);
+    check_consistent::<SizedVEBTree<20>>(20);
+}
+
+#[test]
+fn arbitrary_from_empty_data() {
+    let tree = SizedVEBTree::<16>::arbitrary(&mut Unstructured::new(&[])).unwrap();
+    assert!(tree.is_empty());
+}
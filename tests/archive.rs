@@ -0,0 +1,72 @@
+#![cfg(feature = "rkyv")]
+
+use flat_veb::{SizedVEBTree, VEBTree};
+use rkyv::{Deserialize, Infallible};
+
+#[test]
+fn archived_tree_answers_queries() {
+    let mut tree = SizedVEBTree::<16>::new();
+    for x in [1337, 5, 42, 0, 65535] {
+        tree.insert(x);
+    }
+
+    let bytes = rkyv::to_bytes::<_, 256>(&tree).unwrap();
+    let archived = unsafe { rkyv::archived_root::<SizedVEBTree<16>>(&bytes) };
+
+    assert_eq!(archived.capacity(), 1 << 16);
+    assert_eq!(archived.len(), 5);
+    for x in [0, 5, 42, 1337, 65535] {
+        assert!(archived.contains(x));
+    }
+    for x in [1, 4, 43, 1336, 65534] {
+        assert!(!archived.contains(x));
+    }
+    assert_eq!(archived.next(43), Some(1337));
+    assert_eq!(archived.next(1338), Some(65535));
+    assert_eq!(archived.prev(41), Some(5));
+    assert_eq!(archived.prev(65534), Some(1337));
+    assert_eq!(archived.first(), Some(0));
+    assert_eq!(archived.last(), Some(65535));
+    assert!(archived.iter().eq(tree.iter()));
+}
+
+#[test]
+fn round_trips_through_archive() {
+    let mut tree = SizedVEBTree::<12>::new();
+    for x in (3..4096).step_by(7) {
+        tree.insert(x);
+    }
+
+    let bytes = rkyv::to_bytes::<_, 256>(&tree).unwrap();
+    let archived = unsafe { rkyv::archived_root::<SizedVEBTree<12>>(&bytes) };
+    let back: SizedVEBTree<12> = archived.deserialize(&mut Infallible).unwrap();
+    assert_eq!(back, tree);
+}
+
+#[test]
+fn archives_empty_tree() {
+    let tree = SizedVEBTree::<6>::new();
+
+    let bytes = rkyv::to_bytes::<_, 256>(&tree).unwrap();
+    let archived = unsafe { rkyv::archived_root::<SizedVEBTree<6>>(&bytes) };
+    assert!(archived.is_empty());
+    assert_eq!(archived.next(0), None);
+    assert_eq!(archived.prev(63), None);
+
+    let back: SizedVEBTree<6> = archived.deserialize(&mut Infallible).unwrap();
+    assert!(back.is_empty());
+}
+
+#[cfg(feature = "dyn_capacity")]
+#[test]
+fn deserializes_into_dynamic_tree() {
+    let mut tree = SizedVEBTree::<20>::new();
+    tree.insert(123_456);
+
+    let bytes = rkyv::to_bytes::<_, 256>(&tree).unwrap();
+    let archived = unsafe { rkyv::archived_root::<SizedVEBTree<20>>(&bytes) };
+    let back: Box<dyn VEBTree> = archived.deserialize(&mut Infallible).unwrap();
+    assert_eq!(back.capacity(), 1 << 20);
+    assert_eq!(back.first(), Some(123_456));
+    assert_eq!(back.len(), 1);
+}
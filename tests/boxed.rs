@@ -0,0 +1,136 @@
+#![cfg(feature = "dyn_capacity")]
+
+use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+
+#[test]
+fn new_boxed_is_empty() {
+    let tree = flat_veb::new_boxed::<SizedVEBTree<16>>();
+    assert!(tree.is_empty());
+    assert_eq!(*tree, SizedVEBTree::<16>::new());
+}
+
+#[test]
+fn new_boxed_fits_small_stack() {
+    // The tree is over 2 MB, so it would overflow
+    // the stack if it was made there first.
+    std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(|| {
+            let mut tree = flat_veb::new_boxed::<SizedVEBTree<24>>();
+            assert!(tree.memory_bytes() > 64 * 1024);
+            assert!(tree.is_empty());
+
+            for x in [0, 12345, SizedVEBTree::<24>::CAPACITY - 1] {
+                assert!(tree.insert(x));
+            }
+            assert!(tree.iter().eq([0, 12345, SizedVEBTree::<24>::CAPACITY - 1]));
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn iterate_dyn_tree() {
+    let mut tree = flat_veb::new_with_capacity(1000);
+    for x in [5, 999, 17, 400] {
+        tree.insert(x);
+    }
+
+    assert!(tree.iter().eq([5, 17, 400, 999]));
+    assert!(tree.iter().rev().eq([999, 400, 17, 5]));
+    assert_eq!(tree.iter().len(), 4);
+    assert!(tree.iter_from(18).eq([400, 999]));
+    assert!(tree.range(6, 400).eq([17]));
+    assert_eq!(tree.gaps().len(), tree.capacity() - 4);
+
+    let mut values = Vec::new();
+    for x in &*tree {
+        values.push(x);
+    }
+    assert_eq!(values, [5, 17, 400, 999]);
+}
+
+#[test]
+fn iteration_stops_after_top_element() {
+    type Big = SizedVEBTree<28>;
+    let top = Big::CAPACITY - 1;
+    let mut tree = flat_veb::new_boxed::<Big>();
+    tree.insert(0);
+    tree.insert(top - 1);
+    tree.insert(top);
+
+    let mut iter = tree.iter();
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.next(), Some(top - 1));
+    assert_eq!(iter.next(), Some(top));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+
+    let mut iter = tree.range(top, usize::MAX);
+    assert_eq!(iter.next(), Some(top));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+
+    let mut iter = tree.iter_from(top - 1);
+    assert_eq!(iter.next_back(), Some(top));
+    assert_eq!(iter.next(), Some(top - 1));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+    assert!(tree.iter().rev().eq([top, top - 1, 0]));
+}
+
+#[test]
+fn downcast_dyn_tree() {
+    let mut tree = flat_veb::new_with_bits(20);
+    tree.insert(12345);
+
+    assert!(tree.as_any().downcast_ref::<SizedVEBTree<16>>().is_none());
+    let sized = tree.as_any().downcast_ref::<SizedVEBTree<20>>().unwrap();
+    assert_eq!(sized.first(), Some(12345));
+
+    let sized = tree
+        .as_any_mut()
+        .downcast_mut::<SizedVEBTree<20>>()
+        .unwrap();
+    sized.insert(7);
+    assert!(tree.iter().eq([7, 12345]));
+}
+
+#[test]
+fn bits_round_trips_through_new_with_bits() {
+    for bits in 0..=24 {
+        let tree = flat_veb::new_with_bits(bits);
+        assert_eq!(tree.bits(), bits);
+        assert_eq!(tree.capacity(), 1 << tree.bits());
+        assert_eq!(
+            flat_veb::new_with_bits(tree.bits()).capacity(),
+            tree.capacity()
+        );
+    }
+}
+
+#[test]
+fn reuse_as_keeps_allocation() {
+    let mut rng = StdRng::seed_from_u64(98);
+    let mut buffer = flat_veb::new_boxed::<SizedVEBTree<20>>();
+    let address = std::ptr::addr_of!(*buffer);
+
+    for _ in 0..10 {
+        let mut other = flat_veb::new_boxed::<SizedVEBTree<20>>();
+        for _ in 0..rng.gen_range(0..1000) {
+            other.insert(rng.gen_range(0..SizedVEBTree::<20>::CAPACITY));
+        }
+
+        buffer.reuse_as(&other);
+        assert_eq!(*buffer, *other);
+        assert!(buffer.iter().eq(other.iter()));
+        assert_eq!(std::ptr::addr_of!(*buffer), address);
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert_eq!(std::ptr::addr_of!(*buffer), address);
+    }
+}
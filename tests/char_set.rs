@@ -0,0 +1,58 @@
+use flat_veb::CharSet;
+use std::collections::BTreeSet;
+
+const TEXT: &str = "The quick brown fox, Ærøskøbing, Ελλάδα, 日本語, and 🦀🦀!";
+
+#[test]
+fn matches_btree() {
+    let set: CharSet = TEXT.chars().collect();
+    let reference: BTreeSet<char> = TEXT.chars().collect();
+
+    assert_eq!(set.len(), reference.len());
+    assert!(set.iter().eq(reference.iter().copied()));
+    assert!(set.iter().rev().eq(reference.iter().rev().copied()));
+    assert_eq!(set.first(), Some(' '));
+    assert_eq!(set.last(), Some('🦀'));
+
+    let probes = [
+        '\0',
+        'a',
+        'z',
+        '~',
+        'Æ',
+        'λ',
+        '\u{d7ff}',
+        '\u{e000}',
+        '語',
+        char::MAX,
+    ];
+    for c in probes.into_iter().chain(TEXT.chars()) {
+        assert_eq!(set.contains(c), reference.contains(&c));
+        assert_eq!(set.next(c), reference.range(c..).next().copied());
+        assert_eq!(set.prev(c), reference.range(..=c).next_back().copied());
+    }
+}
+
+#[test]
+fn insert_and_remove() {
+    let mut set = CharSet::new();
+    assert!(set.is_empty());
+    assert_eq!(set.first(), None);
+    assert_eq!(set.next('a'), None);
+
+    assert!(set.insert('a'));
+    assert!(set.insert(char::MAX));
+    assert!(set.insert('\u{d7ff}'));
+    assert!(!set.insert('a'));
+
+    // Across the surrogate range, which has no chars.
+    assert_eq!(set.next('b'), Some('\u{d7ff}'));
+    assert_eq!(set.next('\u{e000}'), Some(char::MAX));
+    assert_eq!(set.prev('\u{e000}'), Some('\u{d7ff}'));
+
+    assert!(set.remove('a'));
+    assert!(!set.remove('a'));
+    assert_eq!(set.first(), Some('\u{d7ff}'));
+    set.clear();
+    assert!(set.is_empty());
+}
@@ -1,4 +1,5 @@
-use flat_veb::{InnerVEBTree, SizedVEBTree};
+use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+use std::mem::size_of;
 
 #[test]
 fn constants_are_correct() {
@@ -12,7 +13,8 @@ fn constants_are_correct() {
         };
         ($n:expr, T T T) => {};
     }
-    check_bits! {4,
+    check_bits! {0,
+        T T T T
         T T T T T T T T
         T T T T T T T T
         T T T T T T T T
@@ -23,6 +25,78 @@ fn constants_are_correct() {
         T
     }
 
+    let t = flat_veb::new_with_capacity(1);
+    assert_eq!(t.capacity(), 1);
+    let t = flat_veb::new_with_capacity(5);
+    assert_eq!(t.capacity(), 8);
+
     let t = flat_veb::new_with_capacity(1 << 20);
     assert_eq!(t.capacity(), 1 << 20);
 }
+
+#[test]
+fn memory_bytes_is_size_of() {
+    assert_eq!(
+        SizedVEBTree::<4>::new().memory_bytes(),
+        size_of::<SizedVEBTree<4>>()
+    );
+    assert_eq!(
+        SizedVEBTree::<12>::new().memory_bytes(),
+        size_of::<SizedVEBTree<12>>()
+    );
+    assert_eq!(
+        SizedVEBTree::<16>::new().memory_bytes(),
+        size_of::<SizedVEBTree<16>>()
+    );
+
+    let t = flat_veb::new_with_bits(20);
+    assert_eq!(t.memory_bytes(), size_of::<SizedVEBTree<20>>());
+    let t = flat_veb::new_with_bits(24);
+    assert_eq!(t.memory_bytes(), size_of::<SizedVEBTree<24>>());
+    assert!(t.memory_bytes() > 2 << 20);
+}
+
+#[test]
+fn heights_match_tower() {
+    assert_eq!(SizedVEBTree::<4>::HEIGHT, 0);
+    assert_eq!(SizedVEBTree::<8>::HEIGHT, 0);
+    assert_eq!(SizedVEBTree::<12>::HEIGHT, 0);
+    assert_eq!(SizedVEBTree::<13>::HEIGHT, 1);
+    assert_eq!(SizedVEBTree::<16>::HEIGHT, 1);
+    assert_eq!(SizedVEBTree::<24>::HEIGHT, 1);
+    assert_eq!(SizedVEBTree::<25>::HEIGHT, 2);
+    assert_eq!(SizedVEBTree::<32>::HEIGHT, 2);
+    assert_eq!(SizedVEBTree::<49>::HEIGHT, 3);
+
+    assert_eq!(SizedVEBTree::<8>::new().height(), 0);
+    assert_eq!(SizedVEBTree::<16>::new().height(), 1);
+}
+
+#[test]
+fn try_new_with_capacity_reports_maximum() {
+    let t = flat_veb::try_new_with_capacity(100).unwrap();
+    assert_eq!(t.capacity(), 128);
+    let t = flat_veb::try_new_with_capacity(1 << 20).unwrap();
+    assert_eq!(t.capacity(), 1 << 20);
+
+    let maximum = SizedVEBTree::<49>::CAPACITY;
+    let err = flat_veb::try_new_with_capacity(usize::MAX).unwrap_err();
+    assert_eq!(err.requested(), usize::MAX);
+    assert_eq!(err.maximum(), maximum);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Too high capacity: {}. The highest supported capacity is {maximum}.",
+            usize::MAX
+        )
+    );
+
+    let err = flat_veb::try_new_with_capacity(maximum + 1).unwrap_err();
+    assert_eq!(err.requested(), maximum + 1);
+}
+
+#[test]
+#[should_panic(expected = "Too high capacity")]
+fn new_with_capacity_panics_when_too_high() {
+    let _ = flat_veb::new_with_capacity(usize::MAX);
+}
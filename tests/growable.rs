@@ -0,0 +1,70 @@
+#![cfg(feature = "dyn_capacity")]
+
+use flat_veb::GrowableVEBTree;
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+#[test]
+fn increasing_inserts_grow_and_keep_elements() {
+    let mut tree = GrowableVEBTree::new();
+    let mut reference = BTreeSet::new();
+    let mut capacities = vec![tree.capacity()];
+
+    let mut x = 0;
+    while x < 1 << 20 {
+        assert!(tree.insert(x));
+        reference.insert(x);
+        if tree.capacity() != *capacities.last().unwrap() {
+            capacities.push(tree.capacity());
+        }
+        assert!(tree.capacity() > x);
+        assert_eq!(tree.len(), reference.len());
+        x = x * 3 / 2 + 1;
+    }
+
+    assert!(capacities.len() > 5);
+    assert!(capacities.windows(2).all(|w| w[1] >= 2 * w[0]));
+    assert!(tree.iter().eq(reference.iter().copied()));
+    assert!(!tree.insert(0));
+}
+
+#[test]
+fn matches_btree_with_growth() {
+    let mut rng = StdRng::seed_from_u64(94);
+    let mut tree = GrowableVEBTree::with_capacity(16);
+    let mut reference = BTreeSet::new();
+
+    for i in 0..2000 {
+        let x = rng.gen_range(0..16 << (i / 200));
+        if rng.gen_range(0..3) == 0 {
+            assert_eq!(tree.remove(x), reference.remove(&x));
+        } else {
+            assert_eq!(tree.insert(x), reference.insert(x));
+        }
+
+        let y = rng.gen_range(0..32 << (i / 200));
+        assert_eq!(tree.contains(y), reference.contains(&y));
+        assert_eq!(tree.next(y), reference.range(y..).next().copied());
+        assert_eq!(tree.prev(y), reference.range(..=y).next_back().copied());
+    }
+
+    assert_eq!(tree.len(), reference.len());
+    assert_eq!(tree.first(), reference.first().copied());
+    assert_eq!(tree.last(), reference.last().copied());
+    assert!(tree.iter().eq(reference.iter().copied()));
+
+    let capacity = tree.capacity();
+    tree.clear();
+    assert!(tree.is_empty());
+    assert_eq!(tree.capacity(), capacity);
+}
+
+#[test]
+fn collect_grows_to_fit() {
+    let tree: GrowableVEBTree = [5, 70_000, 300].into_iter().collect();
+    assert!(tree.iter().eq([5, 300, 70_000]));
+    assert_eq!(tree.inner().len(), 3);
+    assert!(tree.capacity() > 70_000);
+    assert_eq!(tree.next(70_001), None);
+    assert_eq!(tree.prev(usize::MAX), Some(70_000));
+}
@@ -0,0 +1,49 @@
+use flat_veb::{veb, SizedVEBTree, VEBTree};
+
+#[test]
+fn explicit_size() {
+    let tree: SizedVEBTree<16> = veb![16; 3, 1, 4, 1, 5, 9, 2, 6];
+    assert!(tree.iter().eq([1, 2, 3, 4, 5, 6, 9]));
+
+    let tree = veb![10; (1 << 10) - 1, 0,];
+    assert_eq!(tree.capacity(), 1 << 10);
+    assert!(tree.iter().eq([0, (1 << 10) - 1]));
+
+    let tree = veb![8;];
+    assert!(tree.is_empty());
+    assert_eq!(tree.capacity(), 256);
+}
+
+#[test]
+fn inferred_size() {
+    let tree = veb![1, 2, 3];
+    assert_eq!(tree.capacity(), 4);
+    assert!(tree.iter().eq([1, 2, 3]));
+
+    let tree = veb![255, 0, 17];
+    assert_eq!(tree.capacity(), 256);
+    assert!(tree.iter().eq([0, 17, 255]));
+
+    let tree = veb![256];
+    assert_eq!(tree.capacity(), 512);
+    assert!(tree.iter().eq([256]));
+
+    const BIG: usize = 1 << 20;
+    let tree = veb![BIG, 7];
+    assert_eq!(tree.capacity(), 1 << 21);
+    assert!(tree.iter().eq([7, BIG]));
+
+    let tree = veb![0];
+    assert_eq!(tree.capacity(), 1);
+    assert!(tree.iter().eq([0]));
+}
+
+#[test]
+fn bits_to_fit_is_smallest() {
+    assert_eq!(flat_veb::bits_to_fit(&[]), 0);
+    assert_eq!(flat_veb::bits_to_fit(&[0]), 0);
+    assert_eq!(flat_veb::bits_to_fit(&[1]), 1);
+    assert_eq!(flat_veb::bits_to_fit(&[2, 1]), 2);
+    assert_eq!(flat_veb::bits_to_fit(&[255]), 8);
+    assert_eq!(flat_veb::bits_to_fit(&[3, 256, 4]), 9);
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "dyn_capacity")]
+
+use flat_veb::{VEBMap, VEBTree};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+#[test]
+fn new_map_is_empty() {
+    let map = VEBMap::<12, u32>::new();
+    assert!(map.is_empty());
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.first_key(), None);
+    assert_eq!(map.get(0), None);
+    assert_eq!(map.get(VEBMap::<12, u32>::CAPACITY), None);
+}
+
+#[test]
+fn keys_stay_in_sync_with_values() {
+    let mut rng = StdRng::seed_from_u64(69);
+    let mut map = VEBMap::<16, u64>::new();
+    let mut reference = BTreeMap::new();
+
+    for _ in 0..5000 {
+        let key = rng.gen_range(0..1 << 16);
+        match rng.gen_range(0..3) {
+            0 | 1 => {
+                let value = rng.gen();
+                assert_eq!(map.insert(key, value), reference.insert(key, value));
+            }
+            _ => assert_eq!(map.remove(key), reference.remove(&key)),
+        }
+
+        assert_eq!(map.get(key), reference.get(&key));
+        assert_eq!(map.contains_key(key), reference.contains_key(&key));
+        assert_eq!(map.len(), reference.len());
+        assert_eq!(
+            map.next_key(key),
+            reference.range(key..).next().map(|(&k, _)| k)
+        );
+        assert_eq!(
+            map.prev_key(key),
+            reference.range(..=key).next_back().map(|(&k, _)| k)
+        );
+    }
+
+    assert!(map.keys().iter().eq(reference.keys().copied()));
+    for (&key, value) in &reference {
+        assert_eq!(map.get(key), Some(value));
+    }
+    assert_eq!(map.first_key(), reference.keys().next().copied());
+    assert_eq!(map.last_key(), reference.keys().next_back().copied());
+}
+
+#[test]
+fn get_mut_updates_value() {
+    let mut map = VEBMap::<8, String>::new();
+    map.insert(3, "a".to_string());
+    map.get_mut(3).unwrap().push('b');
+    assert_eq!(map.get(3).map(String::as_str), Some("ab"));
+    assert_eq!(map.get_mut(4), None);
+}
+
+#[test]
+fn clear_and_clone() {
+    let mut map = VEBMap::<20, usize>::new();
+    for key in [0, 5, 1 << 19, (1 << 20) - 1] {
+        map.insert(key, key * 2);
+    }
+
+    let copy = map.clone();
+    assert_eq!(copy, map);
+
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.get(5), None);
+    assert_eq!(copy.get(5), Some(&10));
+    assert_eq!(copy.len(), 4);
+}
+
+#[test]
+fn key_values_match_btree() {
+    let mut rng = StdRng::seed_from_u64(70);
+    let mut map = VEBMap::<12, u32>::new();
+    let mut reference = BTreeMap::new();
+    for _ in 0..300 {
+        let key = rng.gen_range(0..1 << 12);
+        let value = rng.gen();
+        map.insert(key, value);
+        reference.insert(key, value);
+    }
+
+    for key in 0..1 << 12 {
+        assert_eq!(
+            map.next_key_value(key),
+            reference.range(key..).next().map(|(&k, v)| (k, v))
+        );
+        assert_eq!(
+            map.prev_key_value(key),
+            reference.range(..=key).next_back().map(|(&k, v)| (k, v))
+        );
+    }
+
+    let mut walked = Vec::new();
+    let mut current = map.next_key_value(0);
+    while let Some((key, &value)) = current {
+        walked.push((key, value));
+        current = if key + 1 < 1 << 12 {
+            map.next_key_value(key + 1)
+        } else {
+            None
+        };
+    }
+    assert!(walked.into_iter().eq(reference.into_iter()));
+}
+
+#[test]
+fn range_matches_btree() {
+    let mut rng = StdRng::seed_from_u64(71);
+    let mut map = VEBMap::<10, u8>::new();
+    let mut reference = BTreeMap::new();
+    for _ in 0..200 {
+        let key = rng.gen_range(0..1 << 10);
+        let value = rng.gen();
+        map.insert(key, value);
+        reference.insert(key, value);
+    }
+
+    for _ in 0..200 {
+        let lo = rng.gen_range(0..1 << 10);
+        let hi = rng.gen_range(lo..=1 << 10);
+        assert!(map
+            .range(lo, hi)
+            .eq(reference.range(lo..hi).map(|(&k, v)| (k, v))));
+        assert!(map
+            .range(lo, hi)
+            .rev()
+            .eq(reference.range(lo..hi).rev().map(|(&k, v)| (k, v))));
+    }
+
+    assert_eq!(map.range(5, 5).count(), 0);
+    assert_eq!(map.range(500, 100).count(), 0);
+    assert!(map
+        .range(0, usize::MAX)
+        .eq(reference.iter().map(|(&k, v)| (k, v))));
+}
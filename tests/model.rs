@@ -0,0 +1,104 @@
+//! Model based tests, applying random sequences of operations
+//! to both a tree and a `BTreeSet`, and comparing every query.
+//!
+//! When a case fails, proptest shrinks it to a minimal sequence.
+//! For example, if `pop_last` forgot to update `max`,
+//! a failure would shrink to:
+//! ```text
+//! ops = [Insert(0), Insert(1), PopLast]
+//! ```
+//! since `last` is compared after every operation,
+//! and would still return the popped element.
+
+use flat_veb::{InnerVEBTree, SizedVEBTree};
+use proptest::prelude::*;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Insert(usize),
+    Remove(usize),
+    Contains(usize),
+    Next(usize),
+    Prev(usize),
+    First,
+    Last,
+    PopFirst,
+    PopLast,
+}
+
+/// Generates up to `max_len` operations on values in [0, capacity).
+///
+/// The values are biased towards a few small ranges,
+/// so the same elements are often hit repeatedly.
+fn arb_ops(capacity: usize, max_len: usize) -> impl Strategy<Value = Vec<Op>> {
+    let value = prop_oneof![
+        0..capacity.min(8),
+        capacity - capacity.min(8)..capacity,
+        0..capacity,
+    ];
+    let op = prop_oneof![
+        3 => value.clone().prop_map(Op::Insert),
+        2 => value.clone().prop_map(Op::Remove),
+        1 => value.clone().prop_map(Op::Contains),
+        1 => value.clone().prop_map(Op::Next),
+        1 => value.prop_map(Op::Prev),
+        1 => Just(Op::First),
+        1 => Just(Op::Last),
+        1 => Just(Op::PopFirst),
+        1 => Just(Op::PopLast),
+    ];
+    prop::collection::vec(op, 0..max_len)
+}
+
+/// Applies the operations to a tree and a `BTreeSet`,
+/// checking that they agree after every operation.
+fn check_model<T: InnerVEBTree>(ops: &[Op]) -> Result<(), TestCaseError> {
+    let mut tree = T::default();
+    let mut model = BTreeSet::new();
+
+    for &op in ops {
+        match op {
+            Op::Insert(x) => prop_assert_eq!(tree.insert(x), model.insert(x)),
+            Op::Remove(x) => prop_assert_eq!(tree.remove(x), model.remove(&x)),
+            Op::Contains(x) => prop_assert_eq!(tree.contains(x), model.contains(&x)),
+            Op::Next(x) => prop_assert_eq!(tree.next(x), model.range(x..).next().copied()),
+            Op::Prev(x) => prop_assert_eq!(tree.prev(x), model.range(..=x).next_back().copied()),
+            Op::First => prop_assert_eq!(tree.first(), model.first().copied()),
+            Op::Last => prop_assert_eq!(tree.last(), model.last().copied()),
+            Op::PopFirst => prop_assert_eq!(tree.pop_first(), model.pop_first()),
+            Op::PopLast => prop_assert_eq!(tree.pop_last(), model.pop_last()),
+        }
+
+        prop_assert_eq!(tree.len(), model.len());
+        prop_assert_eq!(tree.is_empty(), model.is_empty());
+        prop_assert_eq!(tree.first(), model.first().copied());
+        prop_assert_eq!(tree.last(), model.last().copied());
+    }
+
+    prop_assert!(tree.iter().eq(model.iter().copied()));
+    Ok(())
+}
+
+macro_rules! model_tests {
+    ($($name:ident $n:literal)*) => {
+        proptest! {
+            $(
+                #[test]
+                fn $name(ops in arb_ops(SizedVEBTree::<$n>::CAPACITY, 200)) {
+                    check_model::<SizedVEBTree<$n>>(&ops)?;
+                }
+            )*
+        }
+    };
+}
+
+model_tests!(
+    size_1 1
+    size_4 4
+    size_8 8
+    size_12 12
+    size_13 13
+    size_16 16
+    size_20 20
+);
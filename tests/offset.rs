@@ -0,0 +1,81 @@
+use flat_veb::{OffsetVEBTree, SizedVEBTree};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+type T = OffsetVEBTree<SizedVEBTree<11>>;
+
+#[test]
+fn matches_btree_on_negative_range() {
+    let mut rng = StdRng::seed_from_u64(72);
+    let mut tree = T::new_with_range(-1000, 1000);
+    let mut reference = BTreeSet::new();
+
+    for _ in 0..3000 {
+        let x = rng.gen_range(-1000..1000);
+        if rng.gen() {
+            assert_eq!(tree.insert(x), reference.insert(x));
+        } else {
+            assert_eq!(tree.remove(x), reference.remove(&x));
+        }
+
+        let y = rng.gen_range(-1100..1100);
+        assert_eq!(tree.contains(y), reference.contains(&y));
+        assert_eq!(tree.next(y), reference.range(y..).next().copied());
+        assert_eq!(tree.prev(y), reference.range(..=y).next_back().copied());
+    }
+
+    assert_eq!(tree.len(), reference.len());
+    assert_eq!(tree.first(), reference.first().copied());
+    assert_eq!(tree.last(), reference.last().copied());
+    assert!(tree.iter().eq(reference.iter().copied()));
+    assert!(tree.iter().rev().eq(reference.iter().rev().copied()));
+    assert_eq!(tree.inner().len(), reference.len());
+}
+
+#[test]
+fn next_and_prev_across_zero() {
+    let mut tree = T::new_with_range(-1000, 1000);
+    tree.insert(-3);
+    tree.insert(4);
+
+    assert_eq!(tree.next(-2), Some(4));
+    assert_eq!(tree.next(0), Some(4));
+    assert_eq!(tree.prev(3), Some(-3));
+    assert_eq!(tree.prev(0), Some(-3));
+    assert_eq!(tree.next(5), None);
+    assert_eq!(tree.prev(-4), None);
+
+    tree.insert(-1000);
+    tree.insert(999);
+    assert_eq!(tree.next(i64::MIN), Some(-1000));
+    assert_eq!(tree.prev(i64::MAX), Some(999));
+    assert_eq!(tree.next(1000), None);
+    assert_eq!(tree.prev(-1001), None);
+    assert!(!tree.contains(1000));
+    assert!(!tree.remove(-1001));
+}
+
+#[test]
+fn extreme_range() {
+    let mut tree = OffsetVEBTree::<SizedVEBTree<4>>::new_with_range(i64::MAX - 16, i64::MAX);
+    tree.insert(i64::MAX - 1);
+    tree.insert(i64::MAX - 16);
+    assert!(tree.iter().eq([i64::MAX - 16, i64::MAX - 1]));
+
+    let tree = OffsetVEBTree::<SizedVEBTree<4>>::new_with_range(i64::MIN, i64::MIN);
+    assert_eq!(tree.next(i64::MIN), None);
+    assert_eq!(tree.prev(i64::MIN), None);
+}
+
+#[test]
+#[should_panic(expected = "Value out of range")]
+fn insert_out_of_range_panics() {
+    let mut tree = T::new_with_range(-1000, 1000);
+    tree.insert(1000);
+}
+
+#[test]
+#[should_panic(expected = "Too big range")]
+fn too_big_range_panics() {
+    let _ = T::new_with_range(-1025, 1024);
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "rayon")]
+
+use flat_veb::{InnerVEBTree, SizedVEBTree};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use rayon::iter::ParallelIterator;
+
+fn check_sums<T: InnerVEBTree>(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut tree = T::default();
+    assert_eq!(tree.par_iter().count(), 0);
+
+    for _ in 0..T::CAPACITY.min(2000) {
+        tree.insert(rng.gen_range(0..T::CAPACITY));
+    }
+    tree.insert(0);
+    tree.insert(T::CAPACITY - 1);
+
+    assert_eq!(tree.par_iter().sum::<usize>(), tree.iter().sum::<usize>());
+    assert_eq!(tree.par_iter().count(), tree.len());
+
+    let mut values = tree.par_iter().collect::<Vec<_>>();
+    values.sort_unstable();
+    assert!(tree.iter().eq(values));
+}
+
+#[test]
+fn parallel_sum_matches_sequential() {
+    check_sums::<SizedVEBTree<4>>(4);
+    check_sums::<SizedVEBTree<8>>(8);
+    check_sums::<SizedVEBTree<12>>(12);
+    check_sums::<SizedVEBTree<13>>(13);
+    check_sums::<SizedVEBTree<16>>(16);
+    check_sums::<SizedVEBTree<20>>(20);
+}
+
+#[test]
+fn single_element_is_yielded_once() {
+    let mut tree = SizedVEBTree::<16>::new();
+    tree.insert(4242);
+    assert_eq!(tree.par_iter().collect::<Vec<_>>(), [4242]);
+}
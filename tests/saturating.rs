@@ -0,0 +1,71 @@
+use flat_veb::{InnerVEBTree, SaturatingVEBTree, SizedVEBTree};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+type T = SaturatingVEBTree<SizedVEBTree<10>>;
+const CAPACITY: usize = SizedVEBTree::<10>::CAPACITY;
+
+#[test]
+fn over_capacity_inserts_are_dropped() {
+    let mut tree = T::new();
+    for x in [CAPACITY, CAPACITY + 1, 1 << 40, usize::MAX] {
+        assert!(!tree.insert(x));
+        assert!(!tree.remove(x));
+    }
+    assert!(tree.is_empty());
+    assert_eq!(tree.last(), None);
+
+    assert!(tree.insert(CAPACITY - 1));
+    assert!(!tree.insert(CAPACITY));
+    assert!(tree.iter().eq([CAPACITY - 1]));
+    assert!(!tree.remove(CAPACITY));
+    assert_eq!(tree.len(), 1);
+}
+
+#[test]
+fn queries_clamp_instead_of_panicking() {
+    let mut tree = T::new();
+    for x in [CAPACITY, usize::MAX] {
+        assert!(!tree.contains(x));
+        assert_eq!(tree.next(x), None);
+        assert_eq!(tree.prev(x), None);
+    }
+
+    tree.insert(500);
+    for x in [CAPACITY, usize::MAX] {
+        assert!(!tree.contains(x));
+        assert_eq!(tree.next(x), None);
+        assert_eq!(tree.prev(x), Some(500));
+    }
+
+    tree.insert(CAPACITY - 1);
+    for x in [CAPACITY, usize::MAX] {
+        assert!(tree.contains(x));
+        assert_eq!(tree.next(x), Some(CAPACITY - 1));
+        assert_eq!(tree.prev(x), Some(CAPACITY - 1));
+    }
+}
+
+#[test]
+fn matches_btree_in_range() {
+    let mut rng = StdRng::seed_from_u64(96);
+    let mut tree = T::new();
+    let mut reference = BTreeSet::new();
+
+    for _ in 0..3000 {
+        let x = rng.gen_range(0..2 * CAPACITY);
+        if rng.gen() {
+            assert_eq!(tree.insert(x), x < CAPACITY && reference.insert(x));
+        } else {
+            assert_eq!(tree.remove(x), reference.remove(&x));
+        }
+
+        let y = rng.gen_range(0..CAPACITY);
+        assert_eq!(tree.contains(y), reference.contains(&y));
+        assert_eq!(tree.next(y), reference.range(y..).next().copied());
+        assert_eq!(tree.prev(y), reference.range(..=y).next_back().copied());
+    }
+
+    assert!(tree.iter().eq(reference.iter().copied()));
+    assert_eq!(tree.into_inner(), *tree.inner());
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "serde")]
+
+use flat_veb::{SizedVEBTree, VEBTree};
+
+#[test]
+fn serializes_sorted_elements() {
+    let mut tree = SizedVEBTree::<12>::new();
+    for x in [1337, 5, 42, 0, 4095] {
+        tree.insert(x);
+    }
+
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(json, r#"{"bits":12,"elements":[0,5,42,1337,4095]}"#);
+}
+
+#[test]
+fn serializes_empty_and_small() {
+    let tree = SizedVEBTree::<20>::new();
+    assert_eq!(
+        serde_json::to_string(&tree).unwrap(),
+        r#"{"bits":20,"elements":[]}"#
+    );
+
+    let mut small = SizedVEBTree::<5>::new();
+    small.insert(31);
+    small.insert(3);
+    assert_eq!(
+        serde_json::to_string(&small).unwrap(),
+        r#"{"bits":5,"elements":[3,31]}"#
+    );
+}
+
+#[test]
+fn round_trips_through_json() {
+    let mut tree = SizedVEBTree::<16>::new();
+    for x in (3..1 << 16).step_by(1001) {
+        tree.insert(x);
+    }
+
+    let json = serde_json::to_string(&tree).unwrap();
+    let back: SizedVEBTree<16> = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        back.iter().collect::<Vec<_>>(),
+        tree.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(back.len(), tree.len());
+
+    let fields_swapped = r#"{"elements":[],"bits":16}"#;
+    assert!(serde_json::from_str::<SizedVEBTree<16>>(fields_swapped).is_err());
+}
+
+#[test]
+fn rejects_malformed_input() {
+    assert!(serde_json::from_str::<SizedVEBTree<4>>(r#"{"bits":4,"elements":[1,16]}"#).is_err());
+    assert!(serde_json::from_str::<SizedVEBTree<12>>(r#"{"bits":12,"elements":[4096]}"#).is_err());
+    assert!(serde_json::from_str::<SizedVEBTree<12>>(r#"{"bits":13,"elements":[]}"#).is_err());
+    assert!(serde_json::from_str::<SizedVEBTree<12>>(r#"{"bits":12}"#).is_err());
+    assert!(serde_json::from_str::<SizedVEBTree<12>>(r#"{"bits":12,"elements":[-1]}"#).is_err());
+    assert!(
+        serde_json::from_str::<SizedVEBTree<12>>(r#"{"bits":12,"elements":[1],"extra":0}"#)
+            .is_err()
+    );
+}
+
+#[cfg(feature = "dyn_capacity")]
+#[test]
+fn dynamic_round_trip() {
+    let mut tree = flat_veb::new_with_bits(20);
+    tree.insert(0);
+    tree.insert(123_456);
+    tree.insert((1 << 20) - 1);
+
+    let json = serde_json::to_string(&*tree).unwrap();
+    assert_eq!(json, r#"{"bits":20,"elements":[0,123456,1048575]}"#);
+
+    let back: Box<dyn VEBTree> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.capacity(), 1 << 20);
+    assert!(back.contains(0));
+    assert!(back.contains(123_456));
+    assert!(back.contains((1 << 20) - 1));
+    assert_eq!(back.len(), 3);
+}
+
+#[cfg(feature = "dyn_capacity")]
+#[test]
+fn dynamic_rejects_unsupported_bits() {
+    assert!(serde_json::from_str::<Box<dyn VEBTree>>(r#"{"bits":50,"elements":[]}"#).is_err());
+    assert!(serde_json::from_str::<Box<dyn VEBTree>>(r#"{"bits":200,"elements":[]}"#).is_err());
+    assert!(serde_json::from_str::<Box<dyn VEBTree>>(r#"{"bits":8,"elements":[256]}"#).is_err());
+}
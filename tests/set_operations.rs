@@ -0,0 +1,463 @@
+macro_rules! make_tests {
+    ($name:ident, $n:literal) => {
+        mod $name {
+            use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+            use rand::{prelude::StdRng, Rng, SeedableRng};
+            use std::collections::{BTreeSet, HashSet};
+
+            type T = SizedVEBTree<$n>;
+
+            fn random_pair(rng: &mut StdRng) -> ((T, BTreeSet<usize>), (T, BTreeSet<usize>)) {
+                let make = |rng: &mut StdRng| {
+                    let mut s = T::new();
+                    let mut reference = BTreeSet::new();
+                    let density = rng.gen_range(0..=T::CAPACITY.min(300));
+                    let range = rng.gen_range(1..=T::CAPACITY);
+                    for _ in 0..density {
+                        let x = rng.gen_range(0..range);
+                        s.insert(x);
+                        reference.insert(x);
+                    }
+                    (s, reference)
+                };
+                (make(rng), make(rng))
+            }
+
+            fn check(s: &T, reference: &BTreeSet<usize>) {
+                assert_eq!(
+                    s.iter().collect::<Vec<_>>(),
+                    reference.iter().copied().collect::<Vec<_>>()
+                );
+                assert_eq!(s.len(), reference.len());
+                assert_eq!(s.first(), reference.first().copied());
+                assert_eq!(s.last(), reference.last().copied());
+            }
+
+            #[test]
+            fn union_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    check(&a.union(&b), &ra.union(&rb).copied().collect());
+                    check(&a.union(&T::new()), &ra);
+                    check(&T::new().union(&b), &rb);
+                }
+            }
+
+            #[test]
+            fn bitor_matches_union() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((mut a, ra), (b, rb)) = random_pair(&mut rng);
+                    let expected: BTreeSet<usize> = &ra | &rb;
+                    check(&(&a | &b), &expected);
+                    assert_eq!(&a | &b, a.union(&b));
+                    assert_eq!(&b | &a, a.union(&b));
+
+                    a |= &b;
+                    check(&a, &expected);
+                    a |= &T::new();
+                    check(&a, &expected);
+                }
+            }
+
+            #[test]
+            fn operators_match_named_methods() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+
+                    check(&(&a & &b), &(&ra & &rb));
+                    check(&(&a - &b), &(&ra - &rb));
+                    check(&(&b - &a), &(&rb - &ra));
+                    check(&(&a ^ &b), &(&ra ^ &rb));
+                    assert_eq!(&a & &b, a.intersection(&b));
+                    assert_eq!(&a - &b, a.difference(&b));
+                    assert_eq!(&a ^ &b, a.symmetric_difference(&b));
+
+                    let mut c = a;
+                    c &= &b;
+                    check(&c, &(&ra & &rb));
+                    let mut c = a;
+                    c -= &b;
+                    check(&c, &(&ra - &rb));
+                    let mut c = a;
+                    c ^= &b;
+                    check(&c, &(&ra ^ &rb));
+                    let d = c;
+                    c ^= &d;
+                    assert!(c.is_empty());
+                }
+            }
+
+            #[test]
+            fn complement_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), _) = random_pair(&mut rng);
+                    let c = a.complement();
+                    assert_eq!(a.len() + c.len(), T::CAPACITY);
+                    assert!(a.is_disjoint(&c));
+                    assert!(a.union(&c).is_full());
+                    assert_eq!(c.complement(), a);
+                    if T::CAPACITY <= 1 << 12 {
+                        check(&c, &(0..T::CAPACITY).filter(|x| !ra.contains(x)).collect());
+                    }
+                }
+
+                let empty = T::new();
+                assert!(empty.complement().is_full());
+                assert!(empty.complement().complement().is_empty());
+            }
+
+            #[test]
+            fn intersection_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    check(
+                        &a.intersection(&b),
+                        &ra.intersection(&rb).copied().collect(),
+                    );
+                    check(&a.intersection(&a), &ra);
+
+                    let empty = a.intersection(&T::new());
+                    assert!(empty.is_empty());
+                    check(&empty, &BTreeSet::new());
+                    assert!(T::new().intersection(&b).is_empty());
+                }
+            }
+
+            #[test]
+            fn empty_intersection_is_reusable() {
+                let mut a = T::new();
+                let mut b = T::new();
+                a.insert(1);
+                a.insert(3);
+                b.insert(2);
+
+                let mut c = a.intersection(&b);
+                assert!(c.is_empty());
+                assert_eq!(c.first(), None);
+                assert!(c.insert(2));
+                check(&c, &[2].into_iter().collect());
+            }
+
+            #[test]
+            fn difference_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    check(&a.difference(&b), &ra.difference(&rb).copied().collect());
+                    check(&b.difference(&a), &rb.difference(&ra).copied().collect());
+                    check(&a.difference(&T::new()), &ra);
+                    assert!(a.difference(&a).is_empty());
+                }
+            }
+
+            #[test]
+            fn difference_removing_min() {
+                let mut a = T::new();
+                let mut b = T::new();
+                for x in [1, 2, 5, T::CAPACITY - 1] {
+                    a.insert(x);
+                }
+                b.insert(1);
+                b.insert(T::CAPACITY - 1);
+
+                check(&a.difference(&b), &[2, 5].into_iter().collect());
+            }
+
+            #[test]
+            fn diff_matches_differences() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    let (only_a, only_b) = a.diff(&b);
+                    check(&only_a, &ra.difference(&rb).copied().collect());
+                    check(&only_b, &rb.difference(&ra).copied().collect());
+
+                    let mut patched = a;
+                    patched.difference_with(&only_a);
+                    patched.union_with(&only_b);
+                    assert_eq!(patched, b);
+
+                    let (x, y) = a.diff(&a);
+                    assert!(x.is_empty() && y.is_empty());
+                    assert_eq!(x, T::new());
+                }
+            }
+
+            #[test]
+            fn diff_with_shared_and_moved_min() {
+                let a: T = [0, 1, 5].into_iter().collect();
+                let b: T = [1, 5, T::CAPACITY - 1].into_iter().collect();
+                let (only_a, only_b) = a.diff(&b);
+                check(&only_a, &[0].into_iter().collect());
+                check(&only_b, &[T::CAPACITY - 1].into_iter().collect());
+
+                let (only_b, only_a) = b.diff(&a);
+                check(&only_a, &[0].into_iter().collect());
+                check(&only_b, &[T::CAPACITY - 1].into_iter().collect());
+            }
+
+            #[test]
+            fn jaccard_similarity_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    let intersection = ra.intersection(&rb).count();
+                    let union = ra.union(&rb).count();
+                    assert_eq!(a.intersection_len(&b), intersection);
+                    assert_eq!(a.intersection_len(&b), a.intersection(&b).len());
+
+                    let expected = if union == 0 {
+                        1.0
+                    } else {
+                        intersection as f64 / union as f64
+                    };
+                    assert_eq!(a.jaccard_similarity(&b), expected);
+                    assert_eq!(b.jaccard_similarity(&a), expected);
+                    assert_eq!(a.jaccard_similarity(&a), 1.0);
+                    assert_eq!(a.jaccard_similarity(&a.complement()), 0.0);
+                }
+
+                assert_eq!(T::new().jaccard_similarity(&T::new()), 1.0);
+
+                let a: T = [0, 1, 2, 3].into_iter().collect();
+                let b: T = [2, 3, 4, 5, 6, 7].into_iter().collect();
+                assert_eq!(a.jaccard_similarity(&b), 2.0 / 8.0);
+                let c: T = [8, 9].into_iter().collect();
+                assert_eq!(a.jaccard_similarity(&c), 0.0);
+            }
+
+            #[test]
+            fn hamming_distance_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    assert_eq!(a.hamming_distance(&b), (&a ^ &b).len());
+                    assert_eq!(a.hamming_distance(&b), ra.symmetric_difference(&rb).count());
+                    assert_eq!(b.hamming_distance(&a), a.hamming_distance(&b));
+                    assert_eq!(a.hamming_distance(&a), 0);
+                    assert_eq!(a.hamming_distance(&a.complement()), T::CAPACITY);
+                    assert_eq!(a.hamming_distance(&T::new()), a.len());
+                }
+            }
+
+            #[test]
+            fn symmetric_difference_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    check(
+                        &a.symmetric_difference(&b),
+                        &ra.symmetric_difference(&rb).copied().collect(),
+                    );
+                    check(&a.symmetric_difference(&T::new()), &ra);
+                    assert!(a.symmetric_difference(&a).is_empty());
+                }
+            }
+
+            #[test]
+            fn subset_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    assert_eq!(a.is_subset(&b), ra.is_subset(&rb));
+                    assert_eq!(a.is_superset(&b), ra.is_superset(&rb));
+
+                    let c = a.intersection(&b);
+                    assert!(c.is_subset(&a));
+                    assert!(c.is_subset(&b));
+                    assert!(a.is_superset(&c));
+                    assert!(a.is_subset(&a));
+                    assert!(T::new().is_subset(&a));
+                    assert_eq!(a.is_subset(&T::new()), a.is_empty());
+                }
+            }
+
+            #[test]
+            fn subset_with_other_min_in_block() {
+                let mut a = T::new();
+                let mut b = T::new();
+                a.insert(0);
+                a.insert(1);
+                b.insert(0);
+                assert!(!a.is_subset(&b));
+                b.insert(1);
+                assert!(a.is_subset(&b));
+
+                let mut c = T::new();
+                c.insert(1);
+                assert!(c.is_subset(&a));
+                assert!(!a.is_subset(&c));
+                assert!(c.difference(&a).is_empty());
+            }
+
+            #[test]
+            fn disjoint_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    assert_eq!(a.is_disjoint(&b), ra.is_disjoint(&rb));
+                    assert!(a.is_disjoint(&b.difference(&a)));
+                    assert_eq!(a.is_disjoint(&a), a.is_empty());
+                    assert!(a.is_disjoint(&T::new()));
+
+                    let c = a.intersection(&b);
+                    assert_eq!(a.is_disjoint(&c), c.is_empty());
+                }
+            }
+
+            #[test]
+            fn equality() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    assert_eq!(a == b, ra == rb);
+                    assert_eq!(a, a);
+
+                    let rebuilt = ra.iter().rev().copied().collect::<T>();
+                    assert_eq!(a, rebuilt);
+                    assert_eq!(a.union(&b), b.union(&a));
+                }
+
+                let mut a = T::new();
+                let mut b = T::new();
+                assert_eq!(a, b);
+                a.insert(3);
+                assert_ne!(a, b);
+                b.insert(T::CAPACITY - 1);
+                assert_ne!(a, b);
+                a.remove(3);
+                b.remove(T::CAPACITY - 1);
+                assert_eq!(a, b);
+                assert_eq!(a, T::new());
+            }
+
+            #[test]
+            fn hash_dedups_equal_trees() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut trees = HashSet::new();
+                let mut references = HashSet::new();
+                for _ in 0..20 {
+                    let ((a, ra), (b, rb)) = random_pair(&mut rng);
+                    let rebuilt = ra.iter().rev().copied().collect::<T>();
+                    let emptied = a.union(&b).difference(&b);
+
+                    trees.extend([a, b, rebuilt, emptied, a.union(&b)]);
+                    references.extend([
+                        ra.clone(),
+                        rb.clone(),
+                        ra.difference(&rb).copied().collect(),
+                        ra.union(&rb).copied().collect(),
+                    ]);
+                }
+                trees.insert(T::new());
+                references.insert(BTreeSet::new());
+
+                assert_eq!(trees.len(), references.len());
+            }
+
+            #[test]
+            fn split_off_partitions() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((a, ra), _) = random_pair(&mut rng);
+                    let x = rng.gen_range(0..=T::CAPACITY);
+
+                    let mut low = a;
+                    let high = low.split_off(x);
+                    check(&low, &ra.range(..x).copied().collect());
+                    check(&high, &ra.range(x..).copied().collect());
+                    assert_eq!(low.union(&high), a);
+                    assert!(low.is_disjoint(&high));
+                }
+            }
+
+            #[test]
+            fn split_off_at_ends() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let ((a, ra), _) = random_pair(&mut rng);
+
+                let mut all = a;
+                let none = all.split_off(T::CAPACITY);
+                check(&all, &ra);
+                assert!(none.is_empty());
+
+                let mut none = a;
+                let all = none.split_off(0);
+                check(&all, &ra);
+                assert!(none.is_empty());
+            }
+
+            #[test]
+            fn append_moves_everything() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((mut a, ra), (mut b, rb)) = random_pair(&mut rng);
+                    a.append(&mut b);
+                    check(&a, &ra.union(&rb).copied().collect());
+                    assert!(b.is_empty());
+                    check(&b, &BTreeSet::new());
+
+                    b.insert(T::CAPACITY - 1);
+                    b.insert(0);
+                    check(&b, &BTreeSet::from([0, T::CAPACITY - 1]));
+                }
+            }
+
+            #[test]
+            fn append_to_self_copy() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let ((mut a, ra), _) = random_pair(&mut rng);
+                let mut b = a;
+                a.append(&mut b);
+                check(&a, &ra);
+                assert!(b.is_empty());
+            }
+
+            #[test]
+            fn clone_from_matches_source() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((mut a, _), (b, rb)) = random_pair(&mut rng);
+                    a.clone_from(&b);
+                    check(&a, &rb);
+                    assert_eq!(a, b);
+
+                    a.clone_from(&T::new());
+                    assert!(a.is_empty());
+                    check(&a, &BTreeSet::new());
+                }
+            }
+
+            #[test]
+            fn reuse_as_matches_source() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..50 {
+                    let ((mut a, _), (b, rb)) = random_pair(&mut rng);
+                    a.reuse_as(&b);
+                    check(&a, &rb);
+                    assert_eq!(a, b);
+                }
+            }
+        }
+    };
+}
+
+macro_rules! make_many_tests {
+        ($($name:ident $n:literal)*) => {
+            $(make_tests!{$name, $n})*
+        }
+    }
+
+make_many_tests!(
+    size_4 4
+    size_7 7
+    size_8 8
+    size_9 9
+    size_12 12
+    size_16 16
+);
@@ -0,0 +1,87 @@
+macro_rules! make_tests {
+    ($name:ident, $n:literal) => {
+        mod $name {
+            use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+            use std::collections::BTreeSet;
+
+            type T = SizedVEBTree<$n>;
+
+            /// All `1 << CAPACITY` possible sets, paired with a reference.
+            fn all_sets() -> impl Iterator<Item = (T, BTreeSet<usize>)> {
+                (0..1usize << T::CAPACITY).map(|mask| {
+                    let mut s = T::new();
+                    let mut reference = BTreeSet::new();
+                    for x in (0..T::CAPACITY).filter(|x| mask >> x & 1 == 1) {
+                        s.insert(x);
+                        reference.insert(x);
+                    }
+                    (s, reference)
+                })
+            }
+
+            #[test]
+            fn empty_works() {
+                let mut s = T::new();
+                assert!(s.is_empty());
+                s.clear();
+                assert!(s.is_empty());
+
+                for x in 0..T::CAPACITY {
+                    assert!(!s.contains(x));
+                }
+                assert_eq!(s.first(), None);
+                assert_eq!(s.last(), None);
+            }
+
+            #[test]
+            fn queries_match_btree() {
+                for (s, reference) in all_sets() {
+                    assert_eq!(s.len(), reference.len());
+                    assert_eq!(s.first(), reference.first().copied());
+                    assert_eq!(s.last(), reference.last().copied());
+                    for x in 0..T::CAPACITY {
+                        assert_eq!(s.contains(x), reference.contains(&x));
+                        assert_eq!(s.next(x), reference.range(x..).next().copied());
+                        assert_eq!(s.prev(x), reference.range(..=x).next_back().copied());
+                        assert_eq!(s.rank(x), reference.range(..x).count());
+                        assert_eq!(
+                            s.next_gap(x),
+                            (x..T::CAPACITY).find(|y| !reference.contains(y))
+                        );
+                    }
+                }
+            }
+
+            #[test]
+            fn iterates_both_ways() {
+                for (s, reference) in all_sets() {
+                    assert!(s.iter().eq(reference.iter().copied()));
+                    assert!(s.iter().rev().eq(reference.iter().rev().copied()));
+                }
+            }
+
+            #[test]
+            fn remove_empties() {
+                for (mut s, reference) in all_sets() {
+                    for &x in &reference {
+                        assert!(s.remove(x));
+                    }
+                    assert!(s.is_empty());
+                }
+            }
+        }
+    };
+}
+
+macro_rules! make_many_tests {
+        ($($name:ident $n:literal)*) => {
+            $(make_tests!{$name, $n})*
+        }
+    }
+
+make_many_tests!(
+    size_0 0
+    size_1 1
+    size_2 2
+    size_3 3
+);
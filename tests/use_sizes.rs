@@ -1,7 +1,27 @@
+use flat_veb::{InnerVEBTree, SizedVEBTree};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+/// Makes a set from `T::CAPACITY.min(count)` random inserts,
+/// together with a `BTreeSet` of the same elements to check it against.
+fn populated<T: InnerVEBTree>(rng: &mut StdRng, count: usize) -> (T, BTreeSet<usize>) {
+    let mut s = T::default();
+    let mut reference = BTreeSet::new();
+    for _ in 0..T::CAPACITY.min(count) {
+        let x = rng.gen_range(0..T::CAPACITY);
+        s.insert(x);
+        reference.insert(x);
+    }
+    (s, reference)
+}
+
 macro_rules! make_tests {
     ($name:ident, $n:literal) => {
         mod $name {
+            use super::populated;
             use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+            use rand::{prelude::StdRng, Rng, SeedableRng};
+            use std::collections::BTreeSet;
 
             type T = SizedVEBTree<$n>;
 
@@ -47,6 +67,1637 @@ macro_rules! make_tests {
                 }
                 assert_eq!(iter.next(), None);
             }
+
+            #[test]
+            fn reverse_collect() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (s, reference) = populated::<T>(&mut rng, 1000);
+
+                assert_eq!(
+                    s.iter().rev().collect::<Vec<_>>(),
+                    reference.iter().rev().copied().collect::<Vec<_>>(),
+                );
+            }
+
+            #[test]
+            fn interleaved_ends_meet() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (s, reference) = populated::<T>(&mut rng, 1000);
+
+                let mut iter = s.iter();
+                let mut expected = reference.iter().copied();
+                loop {
+                    let (got, want) = if rng.gen_bool(0.5) {
+                        (iter.next(), expected.next())
+                    } else {
+                        (iter.next_back(), expected.next_back())
+                    };
+                    assert_eq!(got, want);
+                    if got.is_none() {
+                        break;
+                    }
+                }
+                assert_eq!(iter.next(), None);
+                assert_eq!(iter.next_back(), None);
+            }
+
+            #[test]
+            fn len_is_tracked() {
+                let mut s = T::new();
+                assert_eq!(s.len(), 0);
+
+                for x in (0..T::CAPACITY).step_by(3) {
+                    s.insert(x);
+                }
+                assert_eq!(s.len(), s.iter().count());
+
+                s.clear();
+                assert_eq!(s.len(), 0);
+
+                s.insert(5);
+                s.insert(9);
+                assert_eq!(s.len(), 2);
+                assert!(s.remove(5));
+                assert_eq!(s.len(), 1);
+                assert!(s.insert(5));
+                assert_eq!(s.len(), 2);
+                assert!(!s.insert(5));
+                assert_eq!(s.len(), 2);
+            }
+
+            #[test]
+            fn fill_adds_everything() {
+                let mut s = T::new();
+                s.insert(3);
+                s.fill();
+                assert!(!s.is_empty());
+                assert_eq!(s.len(), s.capacity());
+                assert_eq!(s.first(), Some(0));
+                assert_eq!(s.last(), Some(s.capacity() - 1));
+                assert_eq!(s.mex(), T::CAPACITY);
+                if T::CAPACITY <= 1 << 12 {
+                    assert!(s.iter().eq(0..T::CAPACITY));
+                }
+
+                assert!(s.remove(0));
+                assert!(s.remove(T::CAPACITY - 1));
+                assert_eq!(s.first(), Some(1));
+                assert_eq!(s.last(), Some(T::CAPACITY - 2));
+                assert_eq!(s.len(), T::CAPACITY - 2);
+
+                s.clear();
+                s.fill();
+                assert_eq!(s.len(), T::CAPACITY);
+            }
+
+            #[test]
+            fn is_full_after_fill() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                assert!(!s.is_full());
+
+                s.fill();
+                assert!(s.is_full());
+
+                for x in [0, T::CAPACITY - 1, rng.gen_range(0..T::CAPACITY)] {
+                    s.remove(x);
+                    assert!(!s.is_full());
+                    s.insert(x);
+                    assert!(s.is_full());
+                }
+
+                if T::CAPACITY <= 1 << 12 {
+                    s.clear();
+                    for x in 0..T::CAPACITY {
+                        assert!(!s.is_full());
+                        s.insert(x);
+                    }
+                    assert!(s.is_full());
+                }
+            }
+
+            #[test]
+            fn count_matches_iteration() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                assert_eq!(s.count(), 0);
+
+                for _ in 0..20 {
+                    for _ in 0..T::CAPACITY.min(100) {
+                        s.insert(rng.gen_range(0..T::CAPACITY));
+                    }
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    s.insert_range(lo, lo + T::CAPACITY.min(300));
+                    assert_eq!(s.count(), s.iter().count());
+                    assert_eq!(s.count(), s.len());
+                }
+
+                s.fill();
+                assert_eq!(s.count(), T::CAPACITY);
+            }
+
+            #[test]
+            fn len_matches_reference() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let mut reference = BTreeSet::new();
+                let range = T::CAPACITY.min(64);
+
+                for _ in 0..1000 {
+                    let x = rng.gen_range(0..range);
+                    if rng.gen_bool(0.5) {
+                        assert_eq!(s.insert(x), reference.insert(x));
+                    } else {
+                        assert_eq!(s.remove(x), reference.remove(&x));
+                    }
+                    assert_eq!(s.len(), reference.len());
+                    assert_eq!(s.is_empty(), s.len() == 0);
+                }
+            }
+
+            #[test]
+            fn count_gaps_complements_len() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                assert_eq!(s.count_gaps(), T::CAPACITY);
+
+                for _ in 0..200 {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + 100));
+                    match rng.gen_range(0..4) {
+                        0 => s.insert_range(lo, hi),
+                        1 => s.remove_range(lo, hi),
+                        2 => {
+                            s.insert(lo);
+                        }
+                        _ => {
+                            s.remove(lo);
+                        }
+                    }
+                    assert_eq!(s.count_gaps() + s.len(), T::CAPACITY);
+
+                    let dynamic: &dyn VEBTree = &s;
+                    assert_eq!(dynamic.count_gaps(), s.count_gaps());
+                }
+
+                s.clear();
+                assert_eq!(s.count_gaps(), T::CAPACITY);
+            }
+
+            #[test]
+            fn checked_insert_rejects_out_of_range() {
+                let mut s = T::new();
+                assert_eq!(s.checked_insert(0), Ok(true));
+                assert_eq!(s.checked_insert(0), Ok(false));
+                assert_eq!(s.checked_insert(T::CAPACITY - 1), Ok(true));
+
+                for x in [T::CAPACITY, T::CAPACITY + 1, usize::MAX] {
+                    assert_eq!(
+                        s.checked_insert(x),
+                        Err(flat_veb::OutOfBounds {
+                            value: x,
+                            capacity: T::CAPACITY
+                        })
+                    );
+                }
+                assert!(s.iter().eq([0, T::CAPACITY - 1]));
+                assert_eq!(s.len(), 2);
+            }
+
+            #[test]
+            fn checked_queries_reject_out_of_range() {
+                let mut s = T::new();
+                s.insert(1);
+                s.insert(T::CAPACITY - 1);
+
+                assert_eq!(s.checked_contains(1), Ok(true));
+                assert_eq!(s.checked_contains(0), Ok(false));
+                assert_eq!(s.checked_next(0), Ok(Some(1)));
+                assert_eq!(s.checked_next(T::CAPACITY - 1), Ok(Some(T::CAPACITY - 1)));
+                assert_eq!(s.checked_prev(0), Ok(None));
+                assert_eq!(s.checked_prev(T::CAPACITY - 1), Ok(Some(T::CAPACITY - 1)));
+
+                for x in [T::CAPACITY, T::CAPACITY + 1, usize::MAX] {
+                    let err = flat_veb::OutOfBounds {
+                        value: x,
+                        capacity: T::CAPACITY,
+                    };
+                    assert_eq!(s.checked_contains(x), Err(err));
+                    assert_eq!(s.checked_next(x), Err(err));
+                    assert_eq!(s.checked_prev(x), Err(err));
+
+                    let dynamic: &dyn VEBTree = &s;
+                    assert_eq!(dynamic.checked_contains(x), Err(err));
+                }
+            }
+
+            #[test]
+            fn rank_matches_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (s, reference) = populated::<T>(&mut rng, 500);
+
+                assert_eq!(s.rank(0), 0);
+                assert_eq!(s.rank(T::CAPACITY), s.len());
+                assert_eq!(s.rank(usize::MAX), s.len());
+                for _ in 0..200 {
+                    let x = rng.gen_range(0..T::CAPACITY);
+                    assert_eq!(s.rank(x), reference.iter().filter(|&&y| y < x).count());
+                }
+                for &x in &reference {
+                    assert_eq!(s.rank(x), reference.range(..x).count());
+                    assert_eq!(s.rank(x + 1), reference.range(..=x).count());
+                }
+            }
+
+            #[test]
+            fn mex_matches_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let naive = |s: &T| {
+                    (0..T::CAPACITY)
+                        .find(|&x| !s.contains(x))
+                        .unwrap_or(T::CAPACITY)
+                };
+
+                assert_eq!(s.mex(), 0);
+                for _ in 0..20 {
+                    s.clear();
+                    let prefix = rng.gen_range(0..T::CAPACITY.min(3000));
+                    for x in 0..prefix {
+                        s.insert(x);
+                    }
+                    for _ in 0..10 {
+                        s.insert(rng.gen_range(0..T::CAPACITY));
+                    }
+                    assert_eq!(s.mex(), naive(&s));
+                }
+
+                if T::CAPACITY <= 1 << 10 {
+                    for x in 0..T::CAPACITY {
+                        s.insert(x);
+                    }
+                    assert_eq!(s.mex(), T::CAPACITY);
+                    assert_eq!(s.next_gap(0), None);
+                }
+            }
+
+            #[test]
+            fn next_excl_matches_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                assert_eq!(T::new().next_excl(0), None);
+
+                let (mut s, mut reference) = populated::<T>(&mut rng, 500);
+                s.insert(0);
+                reference.insert(0);
+
+                for _ in 0..200 {
+                    let x = rng.gen_range(0..T::CAPACITY);
+                    let next = s.next_excl(x);
+                    assert_ne!(next, Some(x));
+                    assert_eq!(next, reference.iter().copied().find(|&y| y > x));
+                }
+                for &x in &reference {
+                    assert_eq!(s.next_excl(x), reference.range(x + 1..).next().copied());
+                }
+
+                s.insert(T::CAPACITY - 1);
+                assert_eq!(s.next_excl(T::CAPACITY - 1), None);
+                assert_eq!(s.next_excl(T::CAPACITY), None);
+                assert_eq!(s.next_excl(usize::MAX), None);
+            }
+
+            #[test]
+            fn insert_and_return_neighbor_matches_reference() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let mut reference = BTreeSet::new();
+
+                assert_eq!(s.insert_and_return_neighbor(T::CAPACITY - 1), (true, None));
+                reference.insert(T::CAPACITY - 1);
+
+                for _ in 0..T::CAPACITY.min(500) {
+                    let x = rng.gen_range(0..T::CAPACITY);
+                    let successor = reference.range(x + 1..).next().copied();
+                    let inserted = reference.insert(x);
+                    assert_eq!(s.insert_and_return_neighbor(x), (inserted, successor));
+                    assert_eq!(s.len(), reference.len());
+                }
+
+                // Elements already present, including the minimum.
+                for &x in reference.iter().take(50) {
+                    let successor = reference.range(x + 1..).next().copied();
+                    assert_eq!(s.insert_and_return_neighbor(x), (false, successor));
+                }
+                let first = s.first().unwrap();
+                if first > 0 {
+                    assert_eq!(s.insert_and_return_neighbor(0), (true, Some(first)));
+                    reference.insert(0);
+                }
+
+                assert!(s.iter().eq(reference.iter().copied()));
+                assert_eq!(s.last(), reference.last().copied());
+            }
+
+            #[test]
+            fn entry_matches_reference() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let mut reference = BTreeSet::new();
+
+                for _ in 0..T::CAPACITY.min(500) {
+                    let x = rng.gen_range(0..T::CAPACITY);
+                    {
+                        let entry = s.entry(x);
+                        assert_eq!(entry.get(), x);
+                        assert_eq!(entry.is_occupied(), reference.contains(&x));
+                        match entry {
+                            flat_veb::Entry::Occupied(entry) => {
+                                if rng.gen() {
+                                    assert_eq!(entry.remove(), x);
+                                    reference.remove(&x);
+                                }
+                            }
+                            flat_veb::Entry::Vacant(entry) => {
+                                assert_eq!(entry.get(), x);
+                                if rng.gen() {
+                                    entry.insert();
+                                    reference.insert(x);
+                                }
+                            }
+                        }
+                    }
+                    assert_eq!(s.len(), reference.len());
+                }
+                assert!(s.iter().eq(reference.iter().copied()));
+
+                let x = rng.gen_range(0..T::CAPACITY);
+                s.entry(x).or_insert();
+                assert!(s.contains(x));
+                s.entry(x).or_insert();
+                assert!(s.contains(x));
+            }
+
+            #[test]
+            fn remove_only_element() {
+                for x in [0, 1, T::CAPACITY / 2, T::CAPACITY - 1] {
+                    let mut s = T::new();
+                    s.insert(x);
+                    assert!(s.remove(x));
+
+                    assert!(s.is_empty());
+                    assert_eq!(s.first(), None);
+                    assert_eq!(s.last(), None);
+                    assert_eq!(s.next(0), None);
+                    assert_eq!(s.prev(T::CAPACITY - 1), None);
+                    assert!(!s.contains(0));
+                    assert!(!s.remove(x));
+                    assert_eq!(s, T::new());
+
+                    let y = T::CAPACITY - 1 - x;
+                    assert!(s.insert(y));
+                    assert_eq!(s.first(), Some(y));
+                    assert_eq!(s.last(), Some(y));
+                    assert!(s.iter().eq([y]));
+                }
+            }
+
+            #[test]
+            #[cfg(not(debug_assertions))]
+            fn out_of_range_queries_in_release() {
+                let mut s = T::new();
+                for x in [T::CAPACITY, T::CAPACITY + 1, usize::MAX] {
+                    assert!(!s.contains(x));
+                    assert_eq!(s.next(x), None);
+                    assert_eq!(s.prev(x), None);
+                }
+
+                s.insert(0);
+                s.insert(T::CAPACITY - 1);
+                for x in [T::CAPACITY, T::CAPACITY + 1, usize::MAX] {
+                    assert!(!s.contains(x));
+                    assert_eq!(s.next(x), None);
+                    assert_eq!(s.prev(x), None);
+                }
+            }
+
+            #[test]
+            fn owned_iteration_matches_borrowed() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (mut s, _) = populated::<T>(&mut rng, 500);
+                s.insert(T::CAPACITY - 1);
+
+                let owned: Vec<usize> = s.into_iter().collect();
+                assert_eq!(owned, s.iter().collect::<Vec<_>>());
+                assert_eq!(s.into_iter().len(), s.len());
+                assert!(s.into_iter().rev().eq(s.iter().rev()));
+
+                let mut values = Vec::new();
+                for x in s {
+                    values.push(x);
+                }
+                assert_eq!(values, owned);
+                assert_eq!(s.len(), owned.len());
+            }
+
+            #[test]
+            fn toggle_matches_reference() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let mut reference = BTreeSet::new();
+
+                for _ in 0..T::CAPACITY.min(1000) {
+                    let x = rng.gen_range(0..T::CAPACITY);
+                    let present = if reference.remove(&x) {
+                        false
+                    } else {
+                        reference.insert(x)
+                    };
+                    assert_eq!(s.toggle(x), present);
+                    assert_eq!(s.contains(x), present);
+                    assert_eq!(s.len(), reference.len());
+                    assert_eq!(s.first(), reference.first().copied());
+                    assert_eq!(s.last(), reference.last().copied());
+                }
+                assert!(s.iter().eq(reference.iter().copied()));
+
+                let before = s;
+                for x in [0, T::CAPACITY / 2, T::CAPACITY - 1] {
+                    let present = s.contains(x);
+                    assert_eq!(s.toggle(x), !present);
+                    assert_eq!(s.toggle(x), present);
+                    assert_eq!(s, before);
+                }
+
+                for x in before.iter() {
+                    assert!(!s.toggle(x));
+                }
+                assert!(s.is_empty());
+                assert_eq!(s, T::new());
+            }
+
+            #[test]
+            fn toggle_range_matches_toggle() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let mut expected = T::new();
+
+                for _ in 0..30 {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = (lo + rng.gen_range(0..T::CAPACITY.min(2000))).min(T::CAPACITY);
+                    s.toggle_range(lo, hi);
+                    for x in lo..hi {
+                        expected.toggle(x);
+                    }
+                    assert_eq!(s, expected);
+                    assert_eq!(s.len(), expected.len());
+                    assert_eq!(s.first(), expected.first());
+                    assert_eq!(s.last(), expected.last());
+                }
+                assert!(s.iter().eq(expected.iter()));
+
+                let before = s;
+                s.toggle_range(0, usize::MAX);
+                assert_eq!(s, before.complement());
+                s.toggle_range(0, T::CAPACITY);
+                assert_eq!(s, before);
+                s.toggle_range(5, 5);
+                s.toggle_range(T::CAPACITY, usize::MAX);
+                assert_eq!(s, before);
+            }
+
+            #[test]
+            fn replace_moves_present_elements() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let mut reference = BTreeSet::new();
+
+                for _ in 0..T::CAPACITY.min(500) {
+                    let old = rng.gen_range(0..T::CAPACITY);
+                    let new = rng.gen_range(0..T::CAPACITY);
+                    if rng.gen() {
+                        s.insert(old);
+                        reference.insert(old);
+                    }
+
+                    let present = reference.remove(&old);
+                    if present {
+                        reference.insert(new);
+                    }
+                    assert_eq!(s.replace(old, new), present);
+                    assert_eq!(s.len(), reference.len());
+                }
+                assert!(s.iter().eq(reference.iter().copied()));
+
+                let before = s;
+                let x = rng.gen_range(0..T::CAPACITY);
+                assert_eq!(s.replace(x, x), before.contains(x));
+                assert_eq!(s, before);
+            }
+
+            #[test]
+            fn copy_to_slice_truncates() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (s, _) = populated::<T>(&mut rng, 200);
+                let values = s.iter().collect::<Vec<_>>();
+
+                let mut exact = vec![0; s.len()];
+                assert_eq!(s.copy_to_slice(&mut exact), s.len());
+                assert_eq!(exact, values);
+
+                let mut oversized = vec![usize::MAX; s.len() + 3];
+                assert_eq!(s.copy_to_slice(&mut oversized), s.len());
+                assert_eq!(oversized[..s.len()], values);
+                assert!(oversized[s.len()..].iter().all(|&x| x == usize::MAX));
+
+                let mut undersized = vec![0; s.len() / 2];
+                assert_eq!(s.copy_to_slice(&mut undersized), s.len() / 2);
+                assert_eq!(undersized, values[..s.len() / 2]);
+
+                assert_eq!(s.copy_to_slice(&mut []), 0);
+                assert_eq!(T::new().copy_to_slice(&mut exact), 0);
+            }
+
+            #[test]
+            fn runs_match_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let naive = |s: &T| {
+                    let mut runs: Vec<(usize, usize)> = Vec::new();
+                    for x in s.iter() {
+                        match runs.last_mut() {
+                            Some((_, end)) if *end == x => *end += 1,
+                            _ => runs.push((x, x + 1)),
+                        }
+                    }
+                    runs
+                };
+
+                assert_eq!(s.runs().next(), None);
+
+                for _ in 0..T::CAPACITY.min(200) {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + 50));
+                    s.insert_range(lo, hi);
+                    s.remove(rng.gen_range(0..T::CAPACITY));
+                }
+                assert!(s.runs().eq(naive(&s)));
+
+                s.insert(0);
+                s.insert(T::CAPACITY - 1);
+                assert!(s.runs().eq(naive(&s)));
+                assert_eq!(s.runs().next().map(|(start, _)| start), Some(0));
+                assert_eq!(s.runs().last().map(|(_, end)| end), Some(T::CAPACITY));
+
+                s.fill();
+                assert!(s.runs().eq([(0, T::CAPACITY)]));
+            }
+
+            #[test]
+            fn count_runs_matches_runs() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                assert_eq!(s.count_runs(), 0);
+
+                s.extend([0, 1, 2, 5, 6, 9]);
+                assert_eq!(s.count_runs(), 3);
+                s.insert(T::CAPACITY - 1);
+                assert_eq!(s.count_runs(), 4);
+                s.insert_range(3, 5);
+                assert_eq!(s.count_runs(), 3);
+
+                for _ in 0..T::CAPACITY.min(200) {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + 50));
+                    s.insert_range(lo, hi);
+                    s.remove(rng.gen_range(0..T::CAPACITY));
+                    assert_eq!(s.count_runs(), s.runs().count());
+                }
+
+                s.fill();
+                assert_eq!(s.count_runs(), 1);
+                s.remove(T::CAPACITY / 2);
+                assert_eq!(s.count_runs(), 2);
+            }
+
+            #[test]
+            fn longest_run_matches_runs() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                assert_eq!(s.longest_run(), None);
+
+                s.insert(9);
+                assert_eq!(s.longest_run(), Some((9, 1)));
+                s.extend([2, 3, 4, 12, 13, 14]);
+                assert_eq!(s.longest_run(), Some((2, 3)));
+                s.insert(15);
+                assert_eq!(s.longest_run(), Some((12, 4)));
+
+                for _ in 0..T::CAPACITY.min(200) {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + 50));
+                    s.insert_range(lo, hi);
+                    s.remove(rng.gen_range(0..T::CAPACITY));
+
+                    let mut expected = None;
+                    for (start, end) in s.runs() {
+                        if end - start > expected.map_or(0, |(_, len)| len) {
+                            expected = Some((start, end - start));
+                        }
+                    }
+                    assert_eq!(s.longest_run(), expected);
+                }
+
+                s.fill();
+                assert_eq!(s.longest_run(), Some((0, T::CAPACITY)));
+            }
+
+            #[test]
+            fn first_free_run_matches_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let naive = |s: &T, n: usize| {
+                    let mut start = 0;
+                    for x in 0..T::CAPACITY {
+                        if s.contains(x) {
+                            start = x + 1;
+                        } else if x + 1 - start >= n {
+                            return Some(start);
+                        }
+                    }
+                    None
+                };
+
+                assert_eq!(s.first_free_run(0), Some(0));
+                assert_eq!(s.first_free_run(T::CAPACITY), Some(0));
+                assert_eq!(s.first_free_run(T::CAPACITY + 1), None);
+
+                for _ in 0..T::CAPACITY.min(300) {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + 50));
+                    s.insert_range(lo, hi);
+                }
+                s.insert(0);
+
+                if T::CAPACITY <= 1 << 16 {
+                    for n in [1, 2, 3, 5, 8, 20, 100] {
+                        assert_eq!(s.first_free_run(n), naive(&s, n));
+                    }
+                }
+                assert_eq!(s.first_free_run(0), Some(0));
+                assert_eq!(s.first_free_run(1), s.gaps().next());
+
+                s.fill();
+                assert_eq!(s.first_free_run(1), None);
+                s.remove(T::CAPACITY - 1);
+                assert_eq!(s.first_free_run(1), Some(T::CAPACITY - 1));
+                assert_eq!(s.first_free_run(2), None);
+            }
+
+            #[test]
+            fn insert_const_matches_insert() {
+                let mut s = T::new();
+                assert!(s.insert_const::<0>());
+                assert!(s.insert_const::<{ T::CAPACITY - 1 }>());
+                assert!(!s.insert_const::<0>());
+                assert!(!s.insert(T::CAPACITY - 1));
+                assert!(s.iter().eq([0, T::CAPACITY - 1]));
+            }
+
+            #[test]
+            fn select_inverts_rank() {
+                let mut rng = StdRng::seed_from_u64($n);
+                assert_eq!(T::new().select(0), None);
+
+                let (mut s, _) = populated::<T>(&mut rng, 500);
+                s.insert(T::CAPACITY - 1);
+
+                let values: Vec<usize> = s.iter().collect();
+                for (k, &x) in values.iter().enumerate() {
+                    assert_eq!(s.select(k), Some(x));
+                    assert_eq!(s.rank(x), k);
+                }
+                assert_eq!(s.select(values.len()), None);
+                assert_eq!(s.select(usize::MAX), None);
+            }
+
+            #[test]
+            fn nth_matches_stepping() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (s, _) = populated::<T>(&mut rng, 500);
+                let values: Vec<usize> = s.iter().collect();
+
+                for _ in 0..100 {
+                    let k = rng.gen_range(0..values.len() + 3);
+                    let mut stepped = s.iter();
+                    for _ in 0..k {
+                        stepped.next();
+                    }
+                    assert_eq!(s.iter().nth(k), stepped.next());
+                    assert_eq!(s.iter().nth(k), values.get(k).copied());
+
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY);
+                    let mut stepped = s.range(lo, hi);
+                    for _ in 0..k {
+                        stepped.next();
+                    }
+                    assert_eq!(s.range(lo, hi).nth(k), stepped.next());
+                }
+
+                let mut iter = s.iter();
+                let mut expected = values.iter().copied();
+                let mut remaining = values.len();
+                while remaining > 0 {
+                    let k = rng.gen_range(0..4);
+                    assert_eq!(iter.nth(k), expected.nth(k));
+                    remaining = remaining.saturating_sub(k + 1);
+                    assert_eq!(iter.len(), remaining);
+                    assert_eq!(iter.next_back(), expected.next_back());
+                    remaining = remaining.saturating_sub(1);
+                    assert_eq!(iter.len(), remaining);
+                }
+                assert_eq!(iter.nth(0), None);
+                assert_eq!(iter.nth(usize::MAX), None);
+            }
+
+            #[test]
+            fn upgrade_into_keeps_elements() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                assert!(s.upgrade_into::<{ $n + 1 }>().is_empty());
+
+                for _ in 0..T::CAPACITY.min(100) {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + 30));
+                    s.insert_range(lo, hi);
+                    s.insert(rng.gen_range(0..T::CAPACITY));
+                }
+                s.insert(T::CAPACITY - 1);
+
+                let same = s.upgrade_into::<$n>();
+                assert_eq!(same, s);
+
+                let big = s.upgrade_into::<{ $n + 1 }>();
+                assert_eq!(big.len(), s.len());
+                assert!(big.iter().eq(s.iter()));
+                assert_eq!(big.next(T::CAPACITY), None);
+
+                s.fill();
+                let big = s.upgrade_into::<{ $n + 1 }>();
+                assert!(big.runs().eq([(0, T::CAPACITY)]));
+            }
+
+            #[test]
+            fn pairs_match_windows() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                assert_eq!(s.pairs().next(), None);
+                assert_eq!(s.pairs().len(), 0);
+
+                s.insert(T::CAPACITY - 1);
+                assert_eq!(s.pairs().next(), None);
+                s.insert(0);
+                assert!(s.pairs().eq([(0, T::CAPACITY - 1)]));
+
+                for _ in 0..T::CAPACITY.min(500) {
+                    s.insert(rng.gen_range(0..T::CAPACITY));
+                }
+                let values: Vec<usize> = s.iter().collect();
+                let expected: Vec<(usize, usize)> =
+                    values.windows(2).map(|w| (w[0], w[1])).collect();
+                assert_eq!(s.pairs().len(), expected.len());
+                assert!(s.pairs().eq(expected.iter().copied()));
+                assert!(s.pairs().all(|(x, y)| s.next_excl(x) == Some(y)));
+            }
+
+            #[test]
+            fn half_line_counts_match_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                assert_eq!(T::new().count_lt(0), 0);
+                assert_eq!(T::new().count_ge(0), 0);
+
+                let (mut s, _) = populated::<T>(&mut rng, 500);
+                s.insert(0);
+                s.insert(T::CAPACITY - 1);
+                let values: Vec<usize> = s.iter().collect();
+
+                let boundaries = [
+                    0,
+                    1,
+                    T::CAPACITY - 2,
+                    T::CAPACITY - 1,
+                    T::CAPACITY,
+                    usize::MAX,
+                ];
+                let random = (0..100).map(|_| rng.gen_range(0..T::CAPACITY));
+                for x in boundaries.into_iter().chain(random) {
+                    assert_eq!(s.count_lt(x), values.iter().filter(|&&y| y < x).count());
+                    assert_eq!(s.count_le(x), values.iter().filter(|&&y| y <= x).count());
+                    assert_eq!(s.count_gt(x), values.iter().filter(|&&y| y > x).count());
+                    assert_eq!(s.count_ge(x), values.iter().filter(|&&y| y >= x).count());
+                    assert_eq!(s.count_lt(x) + s.count_ge(x), s.len());
+                }
+
+                assert_eq!(s.count_le(0), 1);
+                assert_eq!(s.count_gt(0), s.len() - 1);
+                assert_eq!(s.count_ge(T::CAPACITY - 1), 1);
+                assert_eq!(s.count_lt(T::CAPACITY - 1), s.len() - 1);
+            }
+
+            #[test]
+            fn prev_excl_matches_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                assert_eq!(T::new().prev_excl(T::CAPACITY - 1), None);
+
+                let (mut s, mut reference) = populated::<T>(&mut rng, 500);
+                s.insert(T::CAPACITY - 1);
+                reference.insert(T::CAPACITY - 1);
+
+                for _ in 0..200 {
+                    let x = rng.gen_range(0..T::CAPACITY);
+                    let prev = s.prev_excl(x);
+                    assert_ne!(prev, Some(x));
+                    assert_eq!(prev, reference.iter().copied().rev().find(|&y| y < x));
+                }
+                for &x in &reference {
+                    assert_eq!(s.prev_excl(x), reference.range(..x).next_back().copied());
+                }
+
+                s.insert(0);
+                assert_eq!(s.prev_excl(0), None);
+                assert_eq!(s.prev_excl(1), Some(0));
+                assert_eq!(s.prev_excl(T::CAPACITY), Some(T::CAPACITY - 1));
+                assert_eq!(s.prev_excl(usize::MAX), Some(T::CAPACITY - 1));
+            }
+
+            #[test]
+            fn closest_matches_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let mut reference = BTreeSet::new();
+                let naive = |reference: &BTreeSet<usize>, x: usize| {
+                    reference
+                        .iter()
+                        .copied()
+                        .min_by_key(|&y| (y.abs_diff(x), y))
+                };
+
+                assert_eq!(s.closest(0), None);
+                s.insert(T::CAPACITY / 2);
+                assert_eq!(s.closest(0), Some(T::CAPACITY / 2));
+                assert_eq!(s.closest(T::CAPACITY - 1), Some(T::CAPACITY / 2));
+                s.clear();
+
+                for _ in 0..T::CAPACITY.min(300) {
+                    let x = rng.gen_range(0..T::CAPACITY);
+                    s.insert(x);
+                    reference.insert(x);
+                }
+
+                for _ in 0..200 {
+                    let x = rng.gen_range(0..T::CAPACITY);
+                    assert_eq!(s.closest(x), naive(&reference, x));
+                }
+                for &x in &reference {
+                    assert_eq!(s.closest(x), Some(x));
+                }
+                assert_eq!(s.closest(0), reference.first().copied());
+                assert_eq!(s.closest(T::CAPACITY - 1), reference.last().copied());
+
+                s.clear();
+                s.insert(2);
+                s.insert(6);
+                assert_eq!(s.closest(4), Some(2));
+                assert_eq!(s.closest(5), Some(6));
+                assert_eq!(s.closest(3), Some(2));
+            }
+
+            #[test]
+            fn shift_by_matches_reference() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let shift = |reference: &BTreeSet<usize>, offset: isize| {
+                    reference
+                        .iter()
+                        .map(|&x| x.checked_add_signed(offset).filter(|&y| y < T::CAPACITY))
+                        .collect::<Option<Vec<_>>>()
+                };
+                // Each shifted set only lives in this closure's frame,
+                // so the biggest sizes don't keep many copies on the stack.
+                let check = |s: &T, reference: &BTreeSet<usize>, offset: isize| {
+                    let Some(expected) = shift(reference, offset) else {
+                        assert!(s.shift_by(offset).is_none());
+                        return;
+                    };
+                    let shifted = s.shift_by(offset).unwrap();
+                    assert!(shifted.iter().eq(expected.iter().copied()));
+                    assert_eq!(shifted.len(), expected.len());
+                    assert_eq!(shifted.first(), expected.first().copied());
+                    assert_eq!(shifted.last(), expected.last().copied());
+                };
+
+                let empty = T::new();
+                assert!(empty.shift_by(isize::MAX).unwrap().is_empty());
+                assert!(empty.shift_by(isize::MIN).unwrap().is_empty());
+
+                let block = 1 << (($n + 1) / 2);
+                for _ in 0..20 {
+                    let mut s = T::new();
+                    let mut reference = BTreeSet::new();
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..T::CAPACITY) + 1;
+                    for _ in 0..(hi - lo).min(300) {
+                        let x = rng.gen_range(lo..hi);
+                        s.insert(x);
+                        reference.insert(x);
+                    }
+
+                    let first = *reference.first().unwrap() as isize;
+                    let last = *reference.last().unwrap() as isize;
+                    let room = T::CAPACITY as isize - 1 - last;
+                    let mut offsets =
+                        vec![0, -first, room, -first - 1, room + 1, T::CAPACITY as isize];
+                    for k in [-2, -1, 1, 2] {
+                        offsets.push(k * block);
+                        offsets.push(k * 64);
+                    }
+                    for _ in 0..10 {
+                        offsets.push(rng.gen_range(-first..=room));
+                    }
+
+                    for offset in offsets {
+                        check(&s, &reference, offset);
+                    }
+
+                    // Elements that would fall off can be dropped first,
+                    // and dropping all of them shifts to an empty set.
+                    s.remove_range(0, hi - 1);
+                    reference.retain(|&x| x >= hi - 1);
+                    check(&s, &reference, -((hi - 1) as isize));
+                    s.clear();
+                    reference.clear();
+                    check(&s, &reference, -((hi - 1) as isize));
+                }
+            }
+
+            #[test]
+            fn gaps_match_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+                let naive = |s: &T| {
+                    (0..T::CAPACITY)
+                        .filter(|&x| !s.contains(x))
+                        .collect::<Vec<_>>()
+                };
+
+                if T::CAPACITY <= 1 << 16 {
+                    assert!(s.gaps().eq(0..T::CAPACITY));
+                    assert_eq!(s.gaps().len(), T::CAPACITY);
+                }
+                assert!(s.gaps().take(10).eq(0..T::CAPACITY.min(10)));
+
+                for _ in 0..T::CAPACITY.min(300) {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + 100));
+                    s.insert_range(lo, hi);
+                }
+                s.insert(0);
+                s.insert(T::CAPACITY - 1);
+
+                if T::CAPACITY <= 1 << 16 {
+                    let expected = naive(&s);
+                    let mut gaps = s.gaps();
+                    assert_eq!(gaps.len(), expected.len());
+                    for &x in &expected {
+                        assert_eq!(gaps.next(), Some(x));
+                    }
+                    assert_eq!(gaps.len(), 0);
+                    assert_eq!(gaps.next(), None);
+                }
+
+                if T::CAPACITY <= 1 << 12 {
+                    s.insert_range(0, T::CAPACITY);
+                    assert_eq!(s.gaps().next(), None);
+                    assert_eq!(s.gaps().len(), 0);
+                }
+            }
+
+            #[test]
+            fn nth_gap_matches_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let mut s = T::new();
+
+                assert_eq!(s.nth_gap(0), Some(0));
+                assert_eq!(s.nth_gap(T::CAPACITY - 1), Some(T::CAPACITY - 1));
+                assert_eq!(s.nth_gap(T::CAPACITY), None);
+
+                for _ in 0..T::CAPACITY.min(300) {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + 100));
+                    s.insert_range(lo, hi);
+                }
+                s.insert(0);
+                s.remove(T::CAPACITY - 1);
+
+                if T::CAPACITY <= 1 << 16 {
+                    let expected = (0..T::CAPACITY)
+                        .filter(|&x| !s.contains(x))
+                        .collect::<Vec<_>>();
+                    for _ in 0..200 {
+                        let k = rng.gen_range(0..expected.len());
+                        assert_eq!(s.nth_gap(k), Some(expected[k]));
+                    }
+                    assert_eq!(s.nth_gap(0), expected.first().copied());
+                    assert_eq!(s.nth_gap(expected.len() - 1), Some(T::CAPACITY - 1));
+                    assert_eq!(s.nth_gap(expected.len()), None);
+                } else {
+                    for k in [0, 1, 10, 100] {
+                        assert_eq!(s.nth_gap(k), s.gaps().nth(k));
+                    }
+                }
+
+                if T::CAPACITY <= 1 << 12 {
+                    s.insert_range(0, T::CAPACITY - 1);
+                    assert_eq!(s.nth_gap(0), Some(T::CAPACITY - 1));
+                    assert_eq!(s.nth_gap(1), None);
+                    s.insert(T::CAPACITY - 1);
+                    assert_eq!(s.nth_gap(0), None);
+                }
+            }
+
+            #[test]
+            fn pop_first_and_last() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (mut s, mut reference) = populated::<T>(&mut rng, 500);
+
+                let mut copy = s;
+                let mut popped = Vec::new();
+                while let Some(x) = copy.pop_first() {
+                    popped.push(x);
+                }
+                assert_eq!(popped, reference.iter().copied().collect::<Vec<_>>());
+                assert!(copy.is_empty());
+                assert_eq!(copy.len(), 0);
+                assert_eq!(copy.first(), None);
+                assert_eq!(copy.pop_last(), None);
+
+                while !reference.is_empty() {
+                    let (got, want) = if rng.gen_bool(0.5) {
+                        (s.pop_first(), reference.pop_first())
+                    } else {
+                        (s.pop_last(), reference.pop_last())
+                    };
+                    assert_eq!(got, want);
+                    assert_eq!(s.len(), reference.len());
+                    assert_eq!(s.first(), reference.first().copied());
+                    assert_eq!(s.last(), reference.last().copied());
+                }
+                assert!(s.is_empty());
+                assert_eq!(s.pop_first(), None);
+
+                assert!(s.insert(1));
+                assert_eq!(s.pop_last(), Some(1));
+                assert!(s.is_empty());
+            }
+
+            #[test]
+            fn drain_empties() {
+                let mut s = T::new();
+                assert_eq!(s.drain().next(), None);
+
+                for x in (0..T::CAPACITY).step_by(5) {
+                    s.insert(x);
+                }
+                let expected = s.iter().collect::<Vec<_>>();
+                assert_eq!(s.drain().collect::<Vec<_>>(), expected);
+                assert!(s.is_empty());
+
+                for x in (0..T::CAPACITY).step_by(5) {
+                    s.insert(x);
+                }
+                let mut drain = s.drain();
+                assert_eq!(drain.len(), expected.len());
+                assert_eq!(drain.next(), Some(0));
+                assert_eq!(drain.next(), Some(5));
+                assert_eq!(drain.len(), expected.len() - 2);
+                drop(drain);
+                assert!(s.is_empty());
+                assert_eq!(s.len(), 0);
+                assert_eq!(s.iter().next(), None);
+            }
+
+            #[test]
+            fn retain_matches_filter() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (mut s, mut reference) = populated::<T>(&mut rng, 500);
+                s.insert(T::CAPACITY - 1);
+                reference.insert(T::CAPACITY - 1);
+
+                let mut visited = Vec::new();
+                s.retain(|x| {
+                    visited.push(x);
+                    x % 2 == 0
+                });
+                assert_eq!(visited, reference.iter().copied().collect::<Vec<_>>());
+
+                reference.retain(|x| x % 2 == 0);
+                assert_eq!(
+                    s.iter().collect::<Vec<_>>(),
+                    reference.iter().copied().collect::<Vec<_>>()
+                );
+                assert_eq!(s.len(), reference.len());
+                assert_eq!(s.first(), reference.first().copied());
+                assert_eq!(s.last(), reference.last().copied());
+
+                s.retain(|_| false);
+                assert!(s.is_empty());
+            }
+
+            #[test]
+            fn range_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (s, reference) = populated::<T>(&mut rng, 500);
+
+                for _ in 0..100 {
+                    let lo = rng.gen_range(0..=T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY);
+                    assert_eq!(
+                        s.range(lo, hi).collect::<Vec<_>>(),
+                        reference.range(lo..hi).copied().collect::<Vec<_>>(),
+                    );
+                    assert_eq!(
+                        s.range(lo, hi).rev().collect::<Vec<_>>(),
+                        reference.range(lo..hi).rev().copied().collect::<Vec<_>>(),
+                    );
+                }
+
+                assert_eq!(s.range(5, 5).next(), None);
+                assert_eq!(s.range(7, 3).next(), None);
+                assert_eq!(s.range(7, 3).next_back(), None);
+                assert_eq!(
+                    s.range(0, usize::MAX).collect::<Vec<_>>(),
+                    s.iter().collect::<Vec<_>>(),
+                );
+            }
+
+            #[test]
+            fn iter_from_is_tail() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (s, _) = populated::<T>(&mut rng, 500);
+                let all = s.iter().collect::<Vec<_>>();
+                assert_eq!(s.iter_from(0).collect::<Vec<_>>(), all);
+
+                for _ in 0..100 {
+                    let k = rng.gen_range(0..=T::CAPACITY);
+                    let tail = all
+                        .iter()
+                        .copied()
+                        .skip_while(|&x| x < k)
+                        .collect::<Vec<_>>();
+                    let iter = s.iter_from(k);
+                    assert_eq!(iter.len(), tail.len());
+                    assert_eq!(iter.collect::<Vec<_>>(), tail);
+                    assert_eq!(
+                        s.iter_from(k).rev().collect::<Vec<_>>(),
+                        tail.iter().rev().copied().collect::<Vec<_>>(),
+                    );
+
+                    // The length is counted the first time it is asked for,
+                    // so it has to match after stepping from both ends too.
+                    let mut iter = s.iter_from(k);
+                    iter.next();
+                    iter.next_back();
+                    assert_eq!(iter.len(), tail.len().saturating_sub(2));
+                }
+            }
+
+            #[test]
+            fn count_range_matches_range() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (s, _) = populated::<T>(&mut rng, 500);
+
+                for _ in 0..200 {
+                    let lo = rng.gen_range(0..=T::CAPACITY);
+                    let hi = rng.gen_range(0..=T::CAPACITY + 5);
+                    assert_eq!(s.count_range(lo, hi), s.range(lo, hi).count());
+                }
+                assert_eq!(s.count_range(0, T::CAPACITY), s.len());
+                assert_eq!(s.count_range(0, usize::MAX), s.len());
+                assert_eq!(s.count_range(3, 3), 0);
+                assert_eq!(s.count_range(usize::MAX, 0), 0);
+            }
+
+            #[test]
+            fn insert_range_matches_loop() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let max_width = T::CAPACITY.min(3000);
+
+                for _ in 0..20 {
+                    let mut s = T::new();
+                    let mut looped = T::new();
+                    for _ in 0..rng.gen_range(0..20) {
+                        let x = rng.gen_range(0..T::CAPACITY);
+                        s.insert(x);
+                        looped.insert(x);
+                    }
+
+                    for _ in 0..3 {
+                        let lo = rng.gen_range(0..T::CAPACITY);
+                        let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + max_width));
+                        s.insert_range(lo, hi);
+                        for x in lo..hi {
+                            looped.insert(x);
+                        }
+
+                        assert_eq!(s.len(), looped.len());
+                        assert_eq!(s.first(), looped.first());
+                        assert_eq!(s.last(), looped.last());
+                        assert_eq!(
+                            s.iter().collect::<Vec<_>>(),
+                            looped.iter().collect::<Vec<_>>()
+                        );
+                        assert_eq!(
+                            s.iter().rev().collect::<Vec<_>>(),
+                            looped.iter().rev().collect::<Vec<_>>(),
+                        );
+                    }
+                }
+
+                let mut s = T::new();
+                s.insert_range(3, 3);
+                s.insert_range(5, 2);
+                assert!(s.is_empty());
+                s.insert_range(T::CAPACITY - 2, usize::MAX);
+                assert_eq!(
+                    s.iter().collect::<Vec<_>>(),
+                    vec![T::CAPACITY - 2, T::CAPACITY - 1]
+                );
+            }
+
+            #[test]
+            fn remove_range_matches_loop() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let max_width = T::CAPACITY.min(3000);
+
+                for _ in 0..20 {
+                    let mut s = T::new();
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + max_width));
+                    s.insert_range(lo, hi);
+                    for _ in 0..rng.gen_range(0..50) {
+                        s.insert(rng.gen_range(0..T::CAPACITY));
+                    }
+                    let mut looped = s;
+
+                    for _ in 0..5 {
+                        let lo = rng.gen_range(0..T::CAPACITY);
+                        let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + max_width));
+                        s.remove_range(lo, hi);
+                        for x in lo..hi {
+                            if looped.contains(x) {
+                                looped.remove(x);
+                            }
+                        }
+
+                        assert_eq!(s.len(), looped.len());
+                        assert_eq!(s.is_empty(), looped.is_empty());
+                        assert_eq!(s.first(), looped.first());
+                        assert_eq!(s.last(), looped.last());
+                        assert_eq!(
+                            s.iter().collect::<Vec<_>>(),
+                            looped.iter().collect::<Vec<_>>()
+                        );
+                        assert_eq!(
+                            s.iter().rev().collect::<Vec<_>>(),
+                            looped.iter().rev().collect::<Vec<_>>(),
+                        );
+                    }
+                }
+
+                let mut s = T::new();
+                s.insert_range(0, T::CAPACITY.min(5000));
+                s.remove_range(0, usize::MAX);
+                assert!(s.is_empty());
+                assert_eq!(s.len(), 0);
+                assert!(s.insert(3));
+                assert_eq!(s.iter().collect::<Vec<_>>(), vec![3]);
+            }
+
+            #[test]
+            fn clear_range_empties_range() {
+                let mut rng = StdRng::seed_from_u64($n);
+                for _ in 0..20 {
+                    let (mut s, mut reference) = populated::<T>(&mut rng, 500);
+
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY);
+                    s.clear_range(lo, hi);
+                    reference.retain(|&x| x < lo || x >= hi);
+
+                    assert_eq!(s.range(lo, hi).next(), None);
+                    assert_eq!(s.count_range(lo, hi), 0);
+                    assert!(s.iter().eq(reference.iter().copied()));
+                    assert_eq!(s.len(), reference.len());
+                    assert_eq!(s.first(), reference.first().copied());
+                    assert_eq!(s.last(), reference.last().copied());
+                }
+
+                let mut s = T::new();
+                s.fill();
+                s.clear_range(0, T::CAPACITY);
+                assert!(s.is_empty());
+            }
+
+            #[test]
+            fn contains_range_matches_scan() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let max_width = T::CAPACITY.min(3000);
+                let mut s = T::new();
+                assert!(s.contains_range(0, 0));
+                assert!(!s.contains_range(0, 1));
+
+                for _ in 0..5 {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + max_width));
+                    s.insert_range(lo, hi);
+                    assert!(s.contains_range(lo, hi));
+                    assert!(!s.contains_range(lo, T::CAPACITY + 1));
+                }
+                s.remove(rng.gen_range(0..T::CAPACITY));
+
+                for _ in 0..100 {
+                    let lo = rng.gen_range(0..T::CAPACITY);
+                    let hi = rng.gen_range(lo..=T::CAPACITY.min(lo + max_width));
+                    assert_eq!(s.contains_range(lo, hi), (lo..hi).all(|x| s.contains(x)));
+                }
+
+                s.insert_range(0, T::CAPACITY);
+                assert!(s.contains_range(0, T::CAPACITY));
+                assert!(s.contains_range(T::CAPACITY, T::CAPACITY));
+                s.remove(T::CAPACITY - 1);
+                assert!(!s.contains_range(0, T::CAPACITY));
+                assert!(s.contains_range(0, T::CAPACITY - 1));
+            }
+
+            #[test]
+            fn from_sorted_slice_matches_collect() {
+                let mut rng = StdRng::seed_from_u64($n);
+                assert!(T::from_sorted_slice(&[]).is_empty());
+
+                for _ in 0..20 {
+                    let range = rng.gen_range(1..=T::CAPACITY);
+                    let mut xs = (0..T::CAPACITY.min(1000))
+                        .map(|_| rng.gen_range(0..range))
+                        .collect::<Vec<_>>();
+                    xs.sort_unstable();
+
+                    let tree = T::from_sorted_slice(&xs);
+                    let collected: T = xs.iter().copied().collect();
+                    assert_eq!(tree, collected);
+                    xs.dedup();
+                    assert!(tree.iter().eq(xs.iter().copied()));
+                    assert_eq!(tree.len(), xs.len());
+                    assert_eq!(tree.first(), xs.first().copied());
+                    assert_eq!(tree.last(), xs.last().copied());
+                }
+
+                let tree = T::from_sorted_slice(&[T::CAPACITY - 1]);
+                assert!(tree.iter().eq([T::CAPACITY - 1]));
+                if T::CAPACITY <= 1 << 16 {
+                    let all = (0..T::CAPACITY).collect::<Vec<_>>();
+                    let tree = T::from_sorted_slice(&all);
+                    assert_eq!(tree.len(), T::CAPACITY);
+                    assert_eq!(tree.mex(), T::CAPACITY);
+                }
+            }
+
+            #[test]
+            fn from_bitmask_matches_bits() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let word_count = T::CAPACITY.div_ceil(64);
+                let capacity_mask = if T::CAPACITY < 64 {
+                    (1 << T::CAPACITY) - 1
+                } else {
+                    !0
+                };
+
+                for density in [0.0, 0.01, 0.5, 0.99, 1.0] {
+                    let words = (0..word_count)
+                        .map(|_| {
+                            (0..64)
+                                .filter(|_| rng.gen_bool(density))
+                                .fold(0u64, |word, b| word | 1 << b)
+                        })
+                        .map(|word| word & capacity_mask)
+                        .collect::<Vec<_>>();
+
+                    let tree = T::from_bitmask(&words);
+                    let expected = (0..T::CAPACITY)
+                        .filter(|&x| words[x / 64] >> (x % 64) & 1 == 1)
+                        .collect::<Vec<_>>();
+                    assert!(tree.iter().eq(expected.iter().copied()));
+                    assert_eq!(tree.len(), expected.len());
+                    assert_eq!(tree.first(), expected.first().copied());
+                    assert_eq!(tree.last(), expected.last().copied());
+                }
+            }
+
+            #[cfg(feature = "alloc")]
+            #[test]
+            fn sorted_vec_matches_iter() {
+                let mut rng = StdRng::seed_from_u64($n);
+                assert!(T::new().to_sorted_vec().is_empty());
+
+                let (mut s, _) = populated::<T>(&mut rng, 500);
+                s.insert(T::CAPACITY - 1);
+
+                let values = s.to_sorted_vec();
+                assert_eq!(values.len(), s.len());
+                assert!(values.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(values, s.iter().collect::<Vec<_>>());
+            }
+
+            #[cfg(feature = "alloc")]
+            #[test]
+            fn bitmask_round_trip() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let capacity_mask = if T::CAPACITY < 64 {
+                    (1 << T::CAPACITY) - 1
+                } else {
+                    !0
+                };
+
+                assert!(T::new().to_bitmask().iter().all(|&word| word == 0));
+                for _ in 0..10 {
+                    let words = (0..T::CAPACITY.div_ceil(64))
+                        .map(|_| rng.gen::<u64>() & rng.gen::<u64>() & capacity_mask)
+                        .collect::<Vec<_>>();
+                    let tree = T::from_bitmask(&words);
+                    assert_eq!(tree.to_bitmask(), words);
+                    assert_eq!(T::from_bitmask(&tree.to_bitmask()), tree);
+                }
+
+                let (s, _) = populated::<T>(&mut rng, 300);
+                let words = s.to_bitmask();
+                assert_eq!(words.len(), T::CAPACITY.div_ceil(64));
+                for x in 0..T::CAPACITY {
+                    assert_eq!(words[x / 64] >> (x % 64) & 1 == 1, s.contains(x));
+                }
+                assert_eq!(T::from_bitmask(&words), s);
+            }
+
+            #[test]
+            fn display_collapses_runs() {
+                let mut s = T::new();
+                assert_eq!(s.to_string(), "{}");
+
+                s.insert_range(1, 6);
+                s.insert(9);
+                s.insert_range(12, 15);
+                assert_eq!(s.to_string(), "{1-5, 9, 12-14}");
+
+                s.insert(0);
+                s.insert(T::CAPACITY - 1);
+                if T::CAPACITY > 16 {
+                    assert_eq!(
+                        s.to_string(),
+                        format!("{{0-5, 9, 12-14, {}}}", T::CAPACITY - 1)
+                    );
+                } else {
+                    assert_eq!(s.to_string(), "{0-5, 9, 12-15}");
+                }
+
+                s.insert_range(0, T::CAPACITY);
+                assert_eq!(s.to_string(), format!("{{0-{}}}", T::CAPACITY - 1));
+
+                s.remove(7);
+                assert_eq!(s.to_string(), format!("{{0-6, 8-{}}}", T::CAPACITY - 1));
+            }
+
+            #[test]
+            fn collect_from_iterator() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let values = (0..T::CAPACITY.min(500))
+                    .map(|_| rng.gen_range(0..T::CAPACITY))
+                    .collect::<Vec<_>>();
+
+                let collected: T = values.iter().copied().collect();
+                let mut inserted = T::new();
+                for &x in &values {
+                    inserted.insert(x);
+                }
+                assert_eq!(collected.len(), inserted.len());
+                for x in 0..T::CAPACITY.min(5000) {
+                    assert_eq!(collected.contains(x), inserted.contains(x));
+                }
+                assert_eq!(
+                    collected.iter().collect::<Vec<_>>(),
+                    inserted.iter().collect::<Vec<_>>()
+                );
+
+                let hi = T::CAPACITY.min(100);
+                let from_range: T = (3..hi).collect();
+                assert_eq!(
+                    from_range.iter().collect::<Vec<_>>(),
+                    (3..hi).collect::<Vec<_>>()
+                );
+            }
+
+            #[test]
+            fn extend_matches_btree() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (mut s, mut reference) = populated::<T>(&mut rng, 100);
+
+                let owned = (0..T::CAPACITY.min(200))
+                    .map(|_| rng.gen_range(0..T::CAPACITY))
+                    .collect::<Vec<_>>();
+                let borrowed = (0..T::CAPACITY.min(200))
+                    .map(|_| rng.gen_range(0..T::CAPACITY))
+                    .collect::<Vec<_>>();
+                s.extend(owned.iter().copied());
+                s.extend(&borrowed);
+                reference.extend(&owned);
+                reference.extend(&borrowed);
+
+                assert_eq!(
+                    s.iter().collect::<Vec<_>>(),
+                    reference.iter().copied().collect::<Vec<_>>()
+                );
+                assert_eq!(s.len(), reference.len());
+                assert_eq!(s.first(), reference.first().copied());
+                assert_eq!(s.last(), reference.last().copied());
+            }
+
+            #[test]
+            fn iterate_reference() {
+                let mut s = T::new();
+                for x in (1..T::CAPACITY).step_by(7) {
+                    s.insert(x);
+                }
+
+                assert_eq!(
+                    (&s).into_iter().collect::<Vec<_>>(),
+                    s.iter().collect::<Vec<_>>()
+                );
+
+                let mut visited = Vec::new();
+                for x in &s {
+                    visited.push(x);
+                }
+                assert_eq!(visited, s.iter().collect::<Vec<_>>());
+            }
+
+            #[cfg(feature = "alloc")]
+            #[test]
+            fn to_btree_set_matches() {
+                let mut rng = StdRng::seed_from_u64($n);
+                assert!(T::new().to_btree_set().is_empty());
+
+                let (s, _) = populated::<T>(&mut rng, 500);
+
+                let set = s.to_btree_set();
+                assert_eq!(set.len(), s.len());
+                assert!(set.iter().copied().eq(s.iter()));
+            }
+
+            #[cfg(feature = "alloc")]
+            #[test]
+            fn from_btree_set_round_trip() {
+                let mut rng = StdRng::seed_from_u64($n);
+                let (s, _) = populated::<T>(&mut rng, 500);
+
+                let back = T::from_btree_set(&s.to_btree_set());
+                assert_eq!(
+                    back.iter().collect::<Vec<_>>(),
+                    s.iter().collect::<Vec<_>>()
+                );
+                assert_eq!(back.len(), s.len());
+                assert!(T::from_btree_set(&BTreeSet::new()).is_empty());
+            }
+
+            #[cfg(all(feature = "alloc", debug_assertions))]
+            #[test]
+            #[should_panic]
+            fn from_btree_set_out_of_range() {
+                let set = [1, T::CAPACITY].into_iter().collect();
+                let _ = T::from_btree_set(&set);
+            }
+
+            #[test]
+            fn exact_size_iter() {
+                let mut s = T::new();
+                for x in (1..T::CAPACITY).step_by(2) {
+                    s.insert(x);
+                }
+
+                let mut iter = s.iter();
+                let mut remaining = s.len();
+                assert_eq!(iter.len(), remaining);
+                while iter.next_back().is_some() && iter.next().is_some() {
+                    remaining = remaining.saturating_sub(2);
+                    assert_eq!(iter.len(), remaining);
+                }
+                assert_eq!(iter.len(), 0);
+            }
         }
     };
 }
@@ -65,6 +1716,30 @@ make_many_tests!(
     size_8 8
     size_9 9
     size_10 10
+    size_12 12
     size_15 15
+    size_16 16
     size_20 20
 );
+
+#[test]
+fn upgrade_into_across_block_sizes() {
+    // 8 -> 16 and 10 -> 20 copy the whole set as a block,
+    // 7 -> 16 puts the set inside a bigger block,
+    // and 13 -> 20 splits the set into blocks smaller than the new ones.
+    use flat_veb::VEBTree as _;
+
+    macro_rules! check {
+        ($n:literal, $m:literal) => {
+            let mut rng = StdRng::seed_from_u64($n);
+            let (s, reference) = populated::<SizedVEBTree<$n>>(&mut rng, 300);
+            let big = s.upgrade_into::<$m>();
+            assert_eq!(big.len(), reference.len());
+            assert!(big.iter().eq(reference.iter().copied()));
+        };
+    }
+    check!(8, 16);
+    check!(10, 20);
+    check!(7, 16);
+    check!(13, 20);
+}
@@ -0,0 +1,77 @@
+use flat_veb::{VEBTree, VEBTreeEnum};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+#[test]
+fn with_bits_picks_smallest_variant() {
+    for bits in 0..=VEBTreeEnum::MAX_BITS {
+        let tree = VEBTreeEnum::with_bits(bits);
+        assert!(tree.is_empty());
+        assert!(tree.capacity() >= 1 << bits);
+
+        let expected = match bits {
+            0..=8 => 1 << 8,
+            9..=12 => 1 << 12,
+            13..=16 => 1 << 16,
+            _ => 1 << 20,
+        };
+        assert_eq!(tree.capacity(), expected);
+        assert_eq!(1 << tree.bits(), expected);
+    }
+
+    assert!(matches!(VEBTreeEnum::with_bits(3), VEBTreeEnum::Bits8(_)));
+    assert!(matches!(VEBTreeEnum::with_bits(12), VEBTreeEnum::Bits12(_)));
+    assert!(matches!(VEBTreeEnum::with_bits(13), VEBTreeEnum::Bits16(_)));
+    assert!(matches!(VEBTreeEnum::with_bits(20), VEBTreeEnum::Bits20(_)));
+}
+
+#[test]
+#[should_panic(expected = "Too high number of bits")]
+fn with_bits_panics_above_max() {
+    let _ = VEBTreeEnum::with_bits(VEBTreeEnum::MAX_BITS + 1);
+}
+
+#[test]
+fn every_variant_matches_btree() {
+    let mut rng = StdRng::seed_from_u64(65);
+    for bits in [8, 12, 16, 20] {
+        let mut tree = VEBTreeEnum::with_bits(bits);
+        let mut reference = BTreeSet::new();
+        let capacity = tree.capacity();
+
+        for _ in 0..2000 {
+            let x = rng.gen_range(0..capacity);
+            match rng.gen_range(0..4) {
+                0 | 1 => assert_eq!(tree.insert(x), reference.insert(x)),
+                2 => assert_eq!(tree.remove(x), reference.remove(&x)),
+                _ => assert_eq!(tree.contains(x), reference.contains(&x)),
+            }
+            assert_eq!(tree.next(x), reference.range(x..).next().copied());
+            assert_eq!(tree.prev(x), reference.range(..=x).next_back().copied());
+            assert_eq!(tree.len(), reference.len());
+        }
+
+        assert_eq!(tree.first(), reference.first().copied());
+        assert_eq!(tree.last(), reference.last().copied());
+        assert_eq!(tree.count(), reference.len());
+        assert_eq!(tree.pop_first(), reference.pop_first());
+        assert_eq!(tree.pop_last(), reference.pop_last());
+
+        tree.clear();
+        assert!(tree.is_empty());
+        tree.fill();
+        assert!(tree.is_full());
+        assert_eq!(tree.len(), capacity);
+    }
+}
+
+#[test]
+fn works_as_trait_object() {
+    let mut tree = VEBTreeEnum::with_bits(16);
+    let dyn_tree: &mut dyn VEBTree = &mut tree;
+    dyn_tree.insert_range(100, 200);
+    dyn_tree.remove(150);
+    assert_eq!(dyn_tree.len(), 99);
+    assert_eq!(dyn_tree.next_gap(100), Some(150));
+    assert_eq!(tree.rank(150), 50);
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "bytemuck")]
+
+use flat_veb::{InnerVEBTree, SizedVEBTree, VEBTree};
+
+#[test]
+fn fixed_up_zeroed_tree_is_empty() {
+    let mut tree = bytemuck::zeroed_box::<SizedVEBTree<24>>();
+    SizedVEBTree::<24>::from_zeroed(&mut tree);
+
+    assert!(tree.is_empty());
+    assert_eq!(tree.len(), 0);
+    assert_eq!(tree.first(), None);
+    assert_eq!(tree.last(), None);
+    assert_eq!(tree.next(0), None);
+    assert!(!tree.contains(0));
+    assert_eq!(tree.iter().next(), None);
+}
+
+#[test]
+fn fixed_up_zeroed_tree_is_usable() {
+    let mut tree = bytemuck::zeroed_box::<SizedVEBTree<16>>();
+    SizedVEBTree::<16>::from_zeroed(&mut tree);
+
+    for x in [0, 7, 4242, 65535] {
+        assert!(tree.insert(x));
+    }
+    assert!(tree.iter().eq([0, 7, 4242, 65535]));
+    assert_eq!(tree.next(8), Some(4242));
+    assert!(tree.remove(0));
+    assert_eq!(tree.first(), Some(7));
+}
+
+#[test]
+fn small_sets_are_empty_when_zeroed() {
+    let tree = bytemuck::zeroed_box::<SizedVEBTree<6>>();
+    assert!(tree.is_empty());
+}